@@ -0,0 +1,12 @@
+use crate::position::Position;
+
+/// Player input which `Game::apply` dispatches to the matching method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    Start,
+    Open(Position),
+    Flag(Position),
+    Unflag(Position),
+    Chord(Position),
+}
@@ -0,0 +1,46 @@
+use crate::error::GameError;
+
+// Reads values written by `Game::to_bytes` one after another, running out of
+// data means the save is truncated.
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { data }
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8], GameError> {
+        if self.data.len() < count {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        let (taken, rest) = self.data.split_at(count);
+        self.data = rest;
+
+        Ok(taken)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, GameError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, GameError> {
+        let mut bytes = [0; 2];
+        bytes.copy_from_slice(self.bytes(2)?);
+
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    pub fn usize(&mut self) -> Result<usize, GameError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.bytes(8)?);
+
+        usize::try_from(u64::from_le_bytes(bytes)).map_err(|_| GameError::InvalidSaveData)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
@@ -0,0 +1,8 @@
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CellState {
+    Hidden,
+    Flagged,
+    Questioned,
+    Open(u8),
+    Mine,
+}
@@ -0,0 +1,10 @@
+/// Which square cells count as neighbours.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Connectivity {
+    /// Eight neighbours, diagonal ones included.
+    #[default]
+    King,
+    /// Four neighbours sharing an edge.
+    Orthogonal,
+}
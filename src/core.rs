@@ -1,47 +1,330 @@
-use std::collections::HashSet;
-
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Write;
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+use crate::action::Action;
+use crate::byte_reader::ByteReader;
+use crate::cell_state::CellState;
+use crate::connectivity::Connectivity;
 use crate::error::GameError;
-use crate::position::Position;
+use crate::history::Move;
+use crate::neighbor_cache::NeighborCache;
+use crate::open_callback::OpenCallback;
+use crate::open_outcome::OpenOutcome;
+use crate::position::{Position, PositionSet};
+use crate::random::XorShift;
 use crate::status::Status;
+use crate::timer::Timer;
+use crate::topology::Topology;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game {
-    pub width: usize,
-    pub height: usize,
-    pub mine_positions: HashSet<Position>,
-    pub open_positions: HashSet<Position>,
-    pub flag_positions: HashSet<Position>,
-    pub status: Status,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    // `width * height`, checked once so it can't overflow later
+    pub(crate) area: usize,
+    pub(crate) mine_positions: PositionSet,
+    // positions holding more than one mine, sorted by position
+    pub(crate) mine_stacks: Vec<(Position, u8)>,
+    pub(crate) open_positions: PositionSet,
+    pub(crate) flag_positions: PositionSet,
+    pub(crate) question_positions: PositionSet,
+    pub(crate) status: Status,
+    pub(crate) safe_first: bool,
+    pub(crate) limit_flags: bool,
+    pub(crate) clamp_remaining: bool,
+    pub(crate) protect_flags: bool,
+    pub(crate) allow_preflag: bool,
+    pub(crate) win_by_flags: bool,
+    pub(crate) training_mode: bool,
+    pub(crate) strict_chord: bool,
+    pub(crate) mercies: u8,
+    pub(crate) mercies_used: u8,
+    pub(crate) editable: bool,
+    pub(crate) wrap: bool,
+    pub(crate) topology: Topology,
+    pub(crate) connectivity: Connectivity,
+    pub(crate) cache_neighbors: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) neighbor_cache: NeighborCache,
+    pub(crate) history: Vec<Move>,
+    pub(crate) lost_at: Option<Position>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) timer: Timer,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) on_open: OpenCallback,
 }
 
 impl Game {
+    /// Most mines stacked on single position, keeps sum of eight neighbours
+    /// within `u8`.
+    pub const MAX_STACKED_MINES: u8 = 31;
+
+    const SAVE_MAGIC: &'static [u8] = b"MSWP";
+    const SAVE_VERSION: u8 = 1;
+
     pub fn new(width: usize, height: usize) -> Result<Game, GameError> {
         if width == 0 || height == 0 {
             return Err(GameError::ZeroFieldArea);
         }
 
+        let area = width.checked_mul(height).ok_or(GameError::AreaOverflow)?;
+
         Ok(Game {
             width,
             height,
-            mine_positions: HashSet::new(),
-            open_positions: HashSet::new(),
-            flag_positions: HashSet::new(),
+            area,
+            mine_positions: PositionSet::new(),
+            mine_stacks: Vec::new(),
+            open_positions: PositionSet::new(),
+            flag_positions: PositionSet::new(),
+            question_positions: PositionSet::new(),
             status: Status::Configuration,
+            safe_first: false,
+            limit_flags: false,
+            clamp_remaining: false,
+            protect_flags: false,
+            allow_preflag: false,
+            win_by_flags: false,
+            training_mode: false,
+            strict_chord: true,
+            mercies: 0,
+            mercies_used: 0,
+            editable: false,
+            wrap: false,
+            topology: Topology::Square,
+            connectivity: Connectivity::King,
+            cache_neighbors: false,
+            neighbor_cache: NeighborCache::default(),
+            history: Vec::new(),
+            lost_at: None,
+            timer: Timer::default(),
+            on_open: OpenCallback::default(),
         })
     }
 
-    fn is_in_bounds(&self, position: &Position) -> bool {
-        if position.0 > self.width - 1 {
-            return false;
+    /// Creates game with `mine_count` mines scattered over the field.
+    /// Same seed and dimensions always produce the same mine positions.
+    /// Mine density of 1.0 or more is rejected with `TooManyMines`.
+    pub fn new_random(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        seed: u64,
+    ) -> Result<Game, GameError> {
+        let mut game = Game::new(width, height)?;
+
+        if mine_count >= game.area {
+            return Err(GameError::TooManyMines);
+        }
+
+        game.scatter_mines(mine_count, seed, &[]);
+
+        Ok(game)
+    }
+
+    /// Creates random game which is already started with `first_click`
+    /// opened. Mines never land on `first_click` and, when `safe_neighbors`
+    /// is set, on its neighbours either.
+    pub fn from_seed_with_first_click_safe(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        seed: u64,
+        first_click: Position,
+        safe_neighbors: bool,
+    ) -> Result<Game, GameError> {
+        let mut game = Game::new(width, height)?;
+
+        if !game.is_in_bounds(&first_click) {
+            return Err(GameError::OutOfBounds(first_click));
+        }
+
+        let mut excluded = vec![first_click];
+
+        if safe_neighbors {
+            excluded.extend(game.neighbors(first_click));
+        }
+
+        if mine_count > game.area - excluded.len() {
+            return Err(GameError::TooManyMines);
+        }
+
+        game.scatter_mines(mine_count, seed, &excluded);
+        game.start()?;
+        game.open(first_click)?;
+
+        Ok(game)
+    }
+
+    /// Mine count for given density, rounded to nearest and clamped so at
+    /// least one position stays without mine. Area too large for `usize` is
+    /// taken as `usize::MAX`.
+    pub fn ideal_mine_count(width: usize, height: usize, density: f32) -> usize {
+        let area = width.saturating_mul(height);
+        // `as` saturates, so negative densities give zero mines
+        let mine_count = (area as f32 * density + 0.5) as usize;
+
+        mine_count.min(area.saturating_sub(1))
+    }
+
+    /// Creates game from text layout, where `*` is mine and `.` is empty
+    /// position. Rows are separated with newlines and must have same length.
+    pub fn from_layout(layout: &str) -> Result<Game, GameError> {
+        let rows: Vec<&str> = layout.lines().collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+
+        let mut game = Game::new(width, rows.len())?;
+
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(GameError::RaggedLayout);
+            }
+
+            for (x, symbol) in row.chars().enumerate() {
+                match symbol {
+                    '*' => game.mine(Position(x, y))?,
+                    '.' => (),
+                    _ => return Err(GameError::UnknownLayoutSymbol(symbol)),
+                }
+            }
+        }
+
+        Ok(game)
+    }
+
+    /// Random game whose `three_bv` is within `min_3bv..=max_3bv`. Tries
+    /// `seed`, `seed + 1` and so on, giving up with `GenerationFailed` after
+    /// `max_attempts` boards out of range.
+    pub fn generate_in_3bv_range(
+        width: usize,
+        height: usize,
+        mine_count: usize,
+        min_3bv: usize,
+        max_3bv: usize,
+        seed: u64,
+        max_attempts: usize,
+    ) -> Result<Game, GameError> {
+        let mut attempt_seed = seed;
+
+        for _ in 0..max_attempts {
+            let game = Game::new_random(width, height, mine_count, attempt_seed)?;
+
+            if (min_3bv..=max_3bv).contains(&game.three_bv()) {
+                return Ok(game);
+            }
+
+            attempt_seed = attempt_seed.wrapping_add(1);
         }
-        if position.1 > self.height - 1 {
-            return false;
+
+        Err(GameError::GenerationFailed)
+    }
+
+    /// Classic 9x9 field with 10 mines.
+    pub fn beginner(seed: u64) -> Result<Game, GameError> {
+        Game::new_random(9, 9, 10, seed)
+    }
+
+    /// Classic 16x16 field with 40 mines.
+    pub fn intermediate(seed: u64) -> Result<Game, GameError> {
+        Game::new_random(16, 16, 40, seed)
+    }
+
+    /// Classic 30x16 field with 99 mines.
+    pub fn expert(seed: u64) -> Result<Game, GameError> {
+        Game::new_random(30, 16, 99, seed)
+    }
+
+    /// ```
+    /// use minesweeper::Game;
+    ///
+    /// let game = Game::new(30, 16).expect("game created");
+    ///
+    /// assert_eq!(game.width(), 30);
+    /// assert_eq!(game.height(), 16);
+    /// ```
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.status == Status::Won
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.status == Status::Lost
+    }
+
+    /// Whether game is finished, either won or lost.
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.mine_positions.len()
+    }
+
+    pub fn open_cells(&self) -> impl Iterator<Item = Position> + '_ {
+        self.open_positions.iter().copied()
+    }
+
+    pub fn flagged_cells(&self) -> impl Iterator<Item = Position> + '_ {
+        self.flag_positions.iter().copied()
+    }
+
+    /// All positions of the field in row-major order: `(0, 0)`, `(1, 0)`,
+    /// ..., `(width - 1, 0)`, `(0, 1)` and so on.
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| Position(x, y)))
+    }
+
+    fn is_in_bounds(&self, position: &Position) -> bool {
+        position.0 < self.width && position.1 < self.height
+    }
+
+    /// Same as `Position::get_relative`, but also rejects positions outside
+    /// of the field.
+    pub fn relative(
+        &self,
+        position: Position,
+        x_dif: isize,
+        y_dif: isize,
+    ) -> Result<Position, GameError> {
+        let relative = position.get_relative(x_dif, y_dif)?;
+
+        if !self.is_in_bounds(&relative) {
+            return Err(GameError::OutOfBounds(relative));
         }
 
-        true
+        Ok(relative)
     }
 
+    /// Places mine on position. Field holds at most `width * height - 1`
+    /// mines, placing more fails with `BoardFull` so there is always
+    /// something to open. Editable game also accepts mines while in progress,
+    /// except on opened positions; mine covering the last hidden safe
+    /// position wins the game.
     pub fn mine(&mut self, position: Position) -> Result<(), GameError> {
-        if self.status != Status::Configuration {
+        let is_editing = self.editable && self.status == Status::InProgress;
+
+        if self.status != Status::Configuration && !is_editing {
             return Err(GameError::IncorrectStatus(
                 self.status,
                 Status::Configuration,
@@ -49,18 +332,35 @@ impl Game {
         }
 
         if !self.is_in_bounds(&position) {
-            return Err(GameError::OutOfBounds);
+            return Err(GameError::OutOfBounds(position));
         }
 
         if self.mine_positions.contains(&position) {
-            return Err(GameError::AlreadyMined);
+            return Err(GameError::AlreadyMined(position));
+        }
+
+        if self.open_positions.contains(&position) {
+            return Err(GameError::AlreadyOpened(position));
+        }
+
+        if self.mine_positions.len() + 1 >= self.area {
+            return Err(GameError::BoardFull);
         }
 
         self.mine_positions.insert(position);
+
+        // mine on the last hidden safe position leaves nothing to open
+        if is_editing && self.open_positions.len() == self.area - self.mine_positions.len() {
+            self.finish(Status::Won);
+        }
+
         Ok(())
     }
 
-    pub fn start(&mut self) -> Result<(), GameError> {
+    /// Same as `mine`, but position which already has mine is left as it is
+    /// instead of failing. Returns whether mine was placed. Only possible
+    /// during configuration.
+    pub fn ensure_mine(&mut self, position: Position) -> Result<bool, GameError> {
         if self.status != Status::Configuration {
             return Err(GameError::IncorrectStatus(
                 self.status,
@@ -68,437 +368,6601 @@ impl Game {
             ));
         }
 
-        self.status = Status::InProgress;
-        Ok(())
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        if self.mine_positions.contains(&position) {
+            return Ok(false);
+        }
+
+        self.mine(position)?;
+        Ok(true)
     }
 
-    pub fn open(&mut self, position: Position) -> Result<(), GameError> {
-        if self.status != Status::InProgress {
-            return Err(GameError::IncorrectStatus(self.status, Status::InProgress));
+    /// Removes mine from position, whole stack if mines are stacked there.
+    /// Only possible during configuration.
+    pub fn unmine(&mut self, position: Position) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
         }
 
         if !self.is_in_bounds(&position) {
-            return Err(GameError::OutOfBounds);
+            return Err(GameError::OutOfBounds(position));
         }
 
-        if self.open_positions.contains(&position) {
-            return Err(GameError::AlreadyOpened);
+        if !self.mine_positions.remove(&position) {
+            return Err(GameError::NotMined(position));
         }
 
-        if self.flag_positions.contains(&position) {
-            self.flag_positions.remove(&position);
+        if let Ok(index) = self.stack_index(position) {
+            self.mine_stacks.remove(index);
         }
 
-        if self.mine_positions.contains(&position) {
-            self.status = Status::Lost;
-            return Ok(());
+        Ok(())
+    }
+
+    /// Same as `mine`, but stacks `count` mines on position, neighbours count
+    /// all of them. Flagging still treats the position as single mine, while
+    /// chording counts flag on it as the whole stack.
+    pub fn mine_with_count(&mut self, position: Position, count: u8) -> Result<(), GameError> {
+        if count == 0 || count > Game::MAX_STACKED_MINES {
+            return Err(GameError::InvalidMineCount(count));
         }
 
-        self.open_positions.insert(position);
+        self.mine(position)?;
 
-        if self.open_positions.len() + self.flag_positions.len() == self.width * self.height {
-            self.status = Status::Won;
+        if count > 1 {
+            let index = self.stack_index(position).unwrap_err();
+            self.mine_stacks.insert(index, (position, count));
         }
 
         Ok(())
     }
 
-    pub fn flag(&mut self, position: Position) -> Result<(), GameError> {
-        if self.status != Status::InProgress {
-            return Err(GameError::IncorrectStatus(self.status, Status::InProgress));
+    /// Number of mines on position, more than one only for stacked mines.
+    pub fn mines_at(&self, position: Position) -> u8 {
+        if !self.mine_positions.contains(&position) {
+            return 0;
         }
 
-        if !self.is_in_bounds(&position) {
-            return Err(GameError::OutOfBounds);
+        match self.stack_index(position) {
+            Ok(index) => self.mine_stacks[index].1,
+            Err(_) => 1,
         }
+    }
 
-        if self.open_positions.contains(&position) {
-            return Err(GameError::AlreadyOpened);
+    fn stack_index(&self, position: Position) -> Result<usize, usize> {
+        self.mine_stacks
+            .binary_search_by_key(&position, |(stacked, _)| *stacked)
+    }
+
+    fn move_mine(&mut self, from: Position, to: Position) {
+        self.mine_positions.remove(&from);
+        self.mine_positions.insert(to);
+
+        if let Ok(index) = self.stack_index(from) {
+            let (_, count) = self.mine_stacks.remove(index);
+            let index = self.stack_index(to).unwrap_err();
+
+            self.mine_stacks.insert(index, (to, count));
         }
+    }
 
-        if self.flag_positions.contains(&position) {
-            return Err(GameError::AlreadyFlagged);
+    /// Places all mines or none of them: on first failure mines already
+    /// placed from this batch are removed and the error is returned.
+    pub fn mine_all(&mut self, positions: &[Position]) -> Result<(), GameError> {
+        for (placed, position) in positions.iter().enumerate() {
+            if let Err(error) = self.mine(*position) {
+                for mine in &positions[..placed] {
+                    self.mine_positions.remove(mine);
+                }
+
+                return Err(error);
+            }
         }
 
-        self.flag_positions.insert(position);
+        Ok(())
+    }
 
-        if self.open_positions.len() + self.flag_positions.len() == self.width * self.height {
-            self.status = Status::Won;
+    /// When enabled, first opened position is never a mine: mine under it
+    /// is moved to the first free position in row-major order.
+    pub fn set_safe_first(&mut self, safe_first: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
         }
 
+        self.safe_first = safe_first;
         Ok(())
     }
 
-    fn check_proximity(&self, position: Position) -> Result<u8, GameError> {
-        if self.status != Status::InProgress {
-            return Err(GameError::IncorrectStatus(self.status, Status::InProgress));
+    /// When enabled, mines can be placed in game in progress, for editors
+    /// previewing the field.
+    pub fn set_editable(&mut self, editable: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
         }
 
-        if !self.is_in_bounds(&position) {
-            return Err(GameError::OutOfBounds);
+        self.editable = editable;
+        Ok(())
+    }
+
+    /// When enabled, field edges wrap around: positions on opposite edges
+    /// are neighbours, so every position has eight of them. Hex field has to
+    /// have even height to wrap, see `HexWrapOddHeight`.
+    pub fn set_wrap(&mut self, wrap: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
         }
 
-        let mut mine_proximity_counter: u8 = 0;
+        Game::check_hex_wrap(self.topology, wrap, self.height)?;
 
-        // TODO: think of better way of getting neigbour relative coordinates
-        let relative_coordinates: Vec<(i8, i8)> = vec![
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ];
-
-        for (x_dif, y_dif) in relative_coordinates.iter() {
-            match position.get_relative((*x_dif).into(), (*y_dif).into()) {
-                Ok(neighbour) => {
-                    if self.is_in_bounds(&neighbour) && self.mine_positions.contains(&neighbour) {
-                        mine_proximity_counter += 1;
-                    }
-                }
-                // don't expect to happen, dif values are too small
-                Err(_) => (),
-            }
+        self.wrap = wrap;
+        self.neighbor_cache.clear();
+        Ok(())
+    }
+
+    /// Changes field dimensions keeping mines which still fit, mines outside
+    /// of the new field are discarded. Fails with `BoardFull` when the kept
+    /// mines would leave no position without mine.
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
         }
 
-        Ok(mine_proximity_counter)
+        if width == 0 || height == 0 {
+            return Err(GameError::ZeroFieldArea);
+        }
+
+        let area = width.checked_mul(height).ok_or(GameError::AreaOverflow)?;
+
+        Game::check_hex_wrap(self.topology, self.wrap, height)?;
+
+        let fits = |position: &Position| position.0 < width && position.1 < height;
+
+        if self.mine_positions.iter().filter(|mine| fits(mine)).count() >= area {
+            return Err(GameError::BoardFull);
+        }
+
+        self.width = width;
+        self.height = height;
+        self.area = area;
+        self.mine_positions.retain(fits);
+        self.mine_stacks.retain(|(position, _)| fits(position));
+        self.open_positions.retain(fits);
+        self.flag_positions.retain(fits);
+        self.question_positions.retain(fits);
+        self.neighbor_cache.clear();
+        Ok(())
+    }
+
+    /// Registers callback called by `open` for every position it opens,
+    /// cascade included, with its mine count, as soon as it is opened.
+    /// Replaces previous callback. Clones of the game don't keep it.
+    pub fn set_on_open(&mut self, callback: Box<dyn FnMut(Position, u8) + Send + Sync>) {
+        self.on_open.set(callback);
+    }
+
+    /// Changes shape of cells, which decides neighbours for mine counts,
+    /// cascades and chords. Wrapping hex field needs even height.
+    pub fn set_topology(&mut self, topology: Topology) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        Game::check_hex_wrap(topology, self.wrap, self.height)?;
+
+        self.topology = topology;
+        self.neighbor_cache.clear();
+        Ok(())
+    }
+
+    /// Chooses whether diagonal positions are neighbours of square cells,
+    /// which decides mine counts, cascades and chords. Hex cells share an
+    /// edge with all their neighbours, so they aren't affected.
+    pub fn set_connectivity(&mut self, connectivity: Connectivity) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.connectivity = connectivity;
+        self.neighbor_cache.clear();
+        Ok(())
+    }
+
+    /// When enabled, neighbours of every position are computed at once on
+    /// first lookup and reused until size or neighbourhood settings change,
+    /// trading memory for speed on large fields. Needs `std`, without it
+    /// neighbours are always computed.
+    pub fn set_cache_neighbors(&mut self, cache_neighbors: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.cache_neighbors = cache_neighbors;
+        self.neighbor_cache.clear();
+        Ok(())
+    }
+
+    /// When enabled, flags can't outnumber mines.
+    pub fn set_limit_flags(&mut self, limit_flags: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.limit_flags = limit_flags;
+        Ok(())
+    }
+
+    /// When enabled, `remaining_mines` never goes below zero and flags can't
+    /// outnumber mines, same as with `set_limit_flags`.
+    pub fn set_clamp_remaining(&mut self, clamp_remaining: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.clamp_remaining = clamp_remaining;
+        Ok(())
+    }
+
+    /// When enabled, flagged positions can't be opened until unflagged.
+    pub fn set_protect_flags(&mut self, protect_flags: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.protect_flags = protect_flags;
+        Ok(())
+    }
+
+    /// When enabled, flags can be placed and removed already during
+    /// configuration and stay once the game starts. Such changes aren't
+    /// recorded as moves.
+    pub fn set_allow_preflag(&mut self, allow_preflag: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.allow_preflag = allow_preflag;
+        Ok(())
+    }
+
+    /// When enabled, game is also won once flags are placed exactly on all
+    /// mines, without any extra flag.
+    pub fn set_win_by_flags(&mut self, win_by_flags: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.win_by_flags = win_by_flags;
+        Ok(())
+    }
+
+    /// When enabled, opening mine flags it instead of losing the game. Win
+    /// still needs every position without mine opened.
+    pub fn set_training_mode(&mut self, training_mode: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.training_mode = training_mode;
+        Ok(())
+    }
+
+    /// When disabled, chord with wrong flag doesn't lose: neighbours holding
+    /// mines are skipped and only the safe ones are opened. Enabled by
+    /// default.
+    pub fn set_strict_chord(&mut self, strict_chord: bool) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.strict_chord = strict_chord;
+        Ok(())
+    }
+
+    /// Number of mine opens per game which flag the mine instead of losing,
+    /// same as in training mode. Once they run out, opening mine loses as
+    /// usual. Undo and reset give mercies back.
+    pub fn set_mercies(&mut self, mercies: u8) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.mercies = mercies;
+        Ok(())
+    }
+
+    /// Mercies not used yet in this game.
+    pub fn mercies_left(&self) -> u8 {
+        self.mercies - self.mercies_used
+    }
+
+    // Odd hex rows are shifted, so wrapping odd number of rows puts two rows
+    // of the same parity next to each other and neighbourhood stops being
+    // mutual.
+    fn check_hex_wrap(topology: Topology, wrap: bool, height: usize) -> Result<(), GameError> {
+        if topology == Topology::Hex && wrap && height % 2 == 1 {
+            return Err(GameError::HexWrapOddHeight);
+        }
+
+        Ok(())
+    }
+
+    fn relocate_mine(&mut self, position: Position) -> Option<Position> {
+        let free = self
+            .positions()
+            .find(|free| *free != position && !self.mine_positions.contains(free))?;
+
+        self.move_mine(position, free);
+
+        Some(free)
+    }
+
+    /// Starts the game. Game is finished right at start only with
+    /// `win_by_flags`, when preflags are placed exactly on the mines.
+    /// Otherwise field without mines is won only after its positions are
+    /// opened, and field where every position is a mine (possible only when
+    /// mines are set directly) stays in progress, as there is nothing to open.
+    pub fn start(&mut self) -> Result<(), GameError> {
+        if self.status != Status::Configuration {
+            return Err(GameError::IncorrectStatus(
+                self.status,
+                Status::Configuration,
+            ));
+        }
+
+        self.status = Status::InProgress;
+        self.timer.start();
+
+        if self.win_by_flags
+            && !self.mine_positions.is_empty()
+            && self.flag_positions == self.mine_positions
+        {
+            self.finish(Status::Won);
+        }
+
+        Ok(())
+    }
+
+    /// Opens position, cascading through positions without mines around.
+    /// Outcome tells what was opened together with mine counts, so UI
+    /// doesn't have to re-read the field. Mine forgiven by training mode or
+    /// mercy counts as hit too.
+    pub fn open(&mut self, position: Position) -> Result<OpenOutcome, GameError> {
+        let opened = self.open_returning(position)?;
+
+        Ok(OpenOutcome {
+            opened: self.with_counts(opened),
+            // safe first open has already moved the mine away by now
+            hit_mine: self.mine_positions.contains(&position),
+            status: self.status,
+        })
+    }
+
+    /// Outcome `open` would have, computed on a copy, so the game itself
+    /// isn't changed and open callback isn't called.
+    pub fn peek_open(&self, position: Position) -> Result<OpenOutcome, GameError> {
+        self.clone().open(position)
+    }
+
+    fn with_counts(&self, positions: PositionSet) -> Vec<(Position, u8)> {
+        positions
+            .into_iter()
+            .map(|position| (position, self.adjacent_mines(position).unwrap_or(0)))
+            .collect()
+    }
+
+    /// Same as `open`, but returns positions opened by this call, including
+    /// ones opened by cascade. Opening a mine returns empty set, as no
+    /// position gets opened.
+    pub fn open_returning(&mut self, position: Position) -> Result<PositionSet, GameError> {
+        self.require_in_progress()?;
+
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        if self.open_positions.contains(&position) {
+            return Err(GameError::AlreadyOpened(position));
+        }
+
+        if self.protect_flags && self.flag_positions.contains(&position) {
+            return Err(GameError::CellFlagged);
+        }
+
+        let flagged = self.flag_positions.remove(&position);
+        let mut questioned = Vec::new();
+
+        if self.question_positions.remove(&position) {
+            questioned.push(position);
+        }
+
+        let mut relocated = None;
+
+        if self.safe_first
+            && self.open_positions.is_empty()
+            && self.mine_positions.contains(&position)
+        {
+            relocated = self.relocate_mine(position);
+        }
+
+        let status = self.status;
+        let mut opened = Vec::new();
+        let forgiven = self.mine_positions.contains(&position)
+            && (self.training_mode || self.mercies_used < self.mercies);
+
+        if forgiven {
+            if !self.training_mode {
+                self.mercies_used += 1;
+            }
+
+            self.flag_positions.insert(position);
+
+            if self.win_by_flags && self.flag_positions == self.mine_positions {
+                self.finish(Status::Won);
+            }
+        } else if self.mine_positions.contains(&position) {
+            self.lost_at = Some(position);
+            self.finish(Status::Lost);
+        } else {
+            let (cascade_opened, cascade_questioned) = self.cascade(position);
+
+            opened = cascade_opened;
+            questioned.extend(cascade_questioned);
+
+            // game is won once every position without mine is opened
+            if self.open_positions.len() == self.area - self.mine_positions.len() {
+                self.finish(Status::Won);
+            }
+        }
+
+        let newly_opened = opened.iter().copied().collect();
+
+        self.history.push(Move::Open {
+            position,
+            opened,
+            flagged,
+            questioned,
+            relocated,
+            status,
+            forgiven,
+        });
+
+        Ok(newly_opened)
+    }
+
+    /// Returns game to configuration keeping mines, so the same field can be
+    /// played again. Settings like safe first open are kept too.
+    pub fn reset(&mut self) {
+        self.open_positions.clear();
+        self.flag_positions.clear();
+        self.question_positions.clear();
+        self.history.clear();
+        self.lost_at = None;
+        self.mercies_used = 0;
+        self.timer.clear();
+        self.status = Status::Configuration;
+    }
+
+    /// Same as `reset`, but starts the game right away, so the same field
+    /// can be replayed without going through configuration.
+    pub fn restart(&mut self) -> Result<(), GameError> {
+        self.reset();
+        self.start()
+    }
+
+    /// Same as `reset`, but also removes all mines.
+    pub fn reset_mines(&mut self) {
+        self.reset();
+        self.mine_positions.clear();
+        self.mine_stacks.clear();
+    }
+
+    /// Player moves need started game, tells apart not started and finished
+    /// one.
+    fn require_in_progress(&self) -> Result<(), GameError> {
+        match self.status {
+            Status::InProgress => Ok(()),
+            Status::Configuration => Err(GameError::NotStarted),
+            Status::Won | Status::Lost => Err(GameError::GameOver),
+        }
+    }
+
+    fn finish(&mut self, status: Status) {
+        self.status = status;
+        self.timer.stop();
+    }
+
+    /// Time since start, stops counting once the game is won or lost.
+    /// `None` until the game is started.
+    #[cfg(feature = "std")]
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.timer.elapsed()
+    }
+
+    pub fn open_xy(&mut self, x: usize, y: usize) -> Result<OpenOutcome, GameError> {
+        self.open(Position::new(x, y))
+    }
+
+    /// Opens positions one by one and returns resulting status. Stops as soon
+    /// as the game is over, remaining positions are left untouched.
+    pub fn open_many(&mut self, positions: &[Position]) -> Result<Status, GameError> {
+        for position in positions {
+            self.open(*position)?;
+
+            if self.status != Status::InProgress {
+                break;
+            }
+        }
+
+        Ok(self.status)
+    }
+
+    pub fn flag(&mut self, position: Position) -> Result<(), GameError> {
+        let is_preflagging = self.allow_preflag && self.status == Status::Configuration;
+
+        if !is_preflagging {
+            self.require_in_progress()?;
+        }
+
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        if self.open_positions.contains(&position) {
+            return Err(GameError::AlreadyOpened(position));
+        }
+
+        if self.flag_positions.contains(&position) {
+            return Err(GameError::AlreadyFlagged(position));
+        }
+
+        if (self.limit_flags || self.clamp_remaining)
+            && self.flag_positions.len() >= self.mine_positions.len()
+        {
+            return Err(GameError::FlagLimitReached);
+        }
+
+        if is_preflagging {
+            self.flag_positions.insert(position);
+            return Ok(());
+        }
+
+        let questioned = self.question_positions.remove(&position);
+        self.flag_positions.insert(position);
+
+        self.history.push(Move::Flag {
+            position,
+            questioned,
+        });
+
+        if self.win_by_flags && self.flag_positions == self.mine_positions {
+            self.finish(Status::Won);
+        }
+
+        Ok(())
+    }
+
+    pub fn flag_xy(&mut self, x: usize, y: usize) -> Result<(), GameError> {
+        self.flag(Position::new(x, y))
+    }
+
+    pub fn unflag(&mut self, position: Position) -> Result<(), GameError> {
+        let is_preflagging = self.allow_preflag && self.status == Status::Configuration;
+
+        if !is_preflagging {
+            self.require_in_progress()?;
+        }
+
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        if !self.flag_positions.contains(&position) {
+            return Err(GameError::NotFlagged);
+        }
+
+        self.flag_positions.remove(&position);
+
+        if is_preflagging {
+            return Ok(());
+        }
+        self.history.push(Move::Unflag(position));
+
+        // removing wrong flag can leave exactly the mines flagged
+        if self.win_by_flags && self.flag_positions == self.mine_positions {
+            self.finish(Status::Won);
+        }
+
+        Ok(())
+    }
+
+    /// Removes all flags, every removal is recorded as separate unflag move.
+    pub fn clear_flags(&mut self) -> Result<(), GameError> {
+        self.require_in_progress()?;
+
+        let flags = core::mem::take(&mut self.flag_positions);
+
+        self.history.extend(flags.into_iter().map(Move::Unflag));
+
+        Ok(())
+    }
+
+    /// Flags position without flag and unflags flagged one, returns whether
+    /// position is flagged afterwards.
+    pub fn toggle_flag(&mut self, position: Position) -> Result<bool, GameError> {
+        if self.flag_positions.contains(&position) {
+            self.unflag(position)?;
+            return Ok(false);
+        }
+
+        self.flag(position)?;
+        Ok(true)
+    }
+
+    /// Marks position with question, replacing flag if there is one.
+    pub fn question(&mut self, position: Position) -> Result<(), GameError> {
+        self.require_in_progress()?;
+
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        if self.open_positions.contains(&position) {
+            return Err(GameError::AlreadyOpened(position));
+        }
+
+        if self.question_positions.contains(&position) {
+            return Err(GameError::AlreadyQuestioned);
+        }
+
+        let flagged = self.flag_positions.remove(&position);
+        self.question_positions.insert(position);
+
+        self.history.push(Move::Question { position, flagged });
+
+        Ok(())
+    }
+
+    /// Reverts last recorded move, open with its whole cascade is reverted
+    /// at once. Loss caused by the move is reverted too.
+    pub fn undo(&mut self) -> Result<(), GameError> {
+        let last_move = self.history.pop().ok_or(GameError::NothingToUndo)?;
+
+        match last_move {
+            Move::Open {
+                position,
+                opened,
+                flagged,
+                questioned,
+                relocated,
+                status,
+                forgiven,
+            } => {
+                for opened_position in opened {
+                    self.open_positions.remove(&opened_position);
+                }
+
+                if forgiven {
+                    self.flag_positions.remove(&position);
+
+                    if !self.training_mode {
+                        self.mercies_used -= 1;
+                    }
+                }
+
+                if flagged {
+                    self.flag_positions.insert(position);
+                }
+
+                self.question_positions.extend(questioned);
+
+                if let Some(free) = relocated {
+                    self.move_mine(free, position);
+                }
+
+                self.status = status;
+
+                if status == Status::InProgress {
+                    self.lost_at = None;
+                    self.timer.resume();
+                }
+            }
+            Move::Flag {
+                position,
+                questioned,
+            } => {
+                self.flag_positions.remove(&position);
+
+                if questioned {
+                    self.question_positions.insert(position);
+                }
+
+                // only flag placed with `win_by_flags` can win the game
+                if self.status == Status::Won {
+                    self.status = Status::InProgress;
+                    self.timer.resume();
+                }
+            }
+            Move::Unflag(position) => {
+                self.flag_positions.insert(position);
+
+                // unflag can win the game with `win_by_flags` too
+                if self.status == Status::Won {
+                    self.status = Status::InProgress;
+                    self.timer.resume();
+                }
+            }
+            Move::Question { position, flagged } => {
+                self.question_positions.remove(&position);
+
+                if flagged {
+                    self.flag_positions.insert(position);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Played moves as text, one per line: symbol (`O` open, `F` flag,
+    /// `U` unflag, `Q` question mark) followed by `x` and `y`, e.g. `O 3 4`.
+    /// Undone moves aren't included.
+    pub fn export_moves(&self) -> String {
+        let mut log = String::new();
+
+        for played in self.history.iter() {
+            let (symbol, position) = match played {
+                Move::Open { position, .. } => ('O', position),
+                Move::Flag { position, .. } => ('F', position),
+                Move::Unflag(position) => ('U', position),
+                Move::Question { position, .. } => ('Q', position),
+            };
+
+            // writing into string can't fail
+            let _ = writeln!(log, "{} {} {}", symbol, position.0, position.1);
+        }
+
+        log
+    }
+
+    /// Plays moves from log made by `export_moves`. Whole log is parsed
+    /// before any move, so malformed log leaves game untouched, while failed
+    /// move keeps moves played before it.
+    pub fn apply_move_log(&mut self, log: &str) -> Result<(), GameError> {
+        let mut moves = Vec::new();
+
+        for line in log.lines().filter(|line| !line.trim().is_empty()) {
+            let mut parts = line.split_whitespace();
+            let symbol = parts.next();
+            let x = parts.next().and_then(|x| x.parse().ok());
+            let y = parts.next().and_then(|y| y.parse().ok());
+
+            match (symbol, x, y, parts.next()) {
+                (Some(symbol @ ("O" | "F" | "U" | "Q")), Some(x), Some(y), None) => {
+                    moves.push((symbol, Position(x, y)))
+                }
+                _ => return Err(GameError::InvalidMoveLog),
+            }
+        }
+
+        for (symbol, position) in moves {
+            match symbol {
+                "O" => {
+                    self.open(position)?;
+                }
+                "F" => self.flag(position)?,
+                "U" => self.unflag(position)?,
+                _ => self.question(position)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Opens all not flagged neighbours of opened position, when number of
+    /// flags around it matches its mine count. Wrong flag means loss.
+    pub fn chord(&mut self, position: Position) -> Result<(), GameError> {
+        self.chord_neighbours(position)?;
+        Ok(())
+    }
+
+    // Does the chord and tells whether any of opened neighbours was a mine,
+    // also when training or mercy forgave it.
+    fn chord_neighbours(&mut self, position: Position) -> Result<bool, GameError> {
+        self.require_in_progress()?;
+
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        if !self.open_positions.contains(&position) {
+            return Err(GameError::NotChordable);
+        }
+
+        if self.adjacent_mines(position)? != self.count_flagged_neighbors(position)? {
+            return Err(GameError::NotChordable);
+        }
+
+        let neighbours = self.neighbors(position);
+        let mut hit_mine = false;
+
+        for neighbour in neighbours {
+            if self.status != Status::InProgress {
+                break;
+            }
+
+            // earlier neighbour could have already opened this one with cascade
+            if self.flag_positions.contains(&neighbour) || self.open_positions.contains(&neighbour)
+            {
+                continue;
+            }
+
+            if !self.strict_chord && self.mine_positions.contains(&neighbour) {
+                continue;
+            }
+
+            hit_mine |= self.open(neighbour)?.hit_mine;
+        }
+
+        Ok(hit_mine)
+    }
+
+    /// Single entry point for player input. Every action reports outcome the
+    /// same way as `open`; ones which can't open anything report no opened
+    /// positions and current status.
+    pub fn apply(&mut self, action: Action) -> Result<OpenOutcome, GameError> {
+        match action {
+            Action::Open(position) => return self.open(position),
+            Action::Chord(position) => {
+                let before = self.open_positions.clone();
+
+                let hit_mine = self.chord_neighbours(position)?;
+                let opened = self.open_positions.difference(&before).copied().collect();
+
+                return Ok(OpenOutcome {
+                    opened: self.with_counts(opened),
+                    hit_mine,
+                    status: self.status,
+                });
+            }
+            Action::Start => self.start()?,
+            Action::Flag(position) => self.flag(position)?,
+            Action::Unflag(position) => self.unflag(position)?,
+        }
+
+        Ok(OpenOutcome {
+            opened: Vec::new(),
+            hit_mine: false,
+            status: self.status,
+        })
+    }
+
+    /// Flags unopened neighbours of opened positions whose mine count equals
+    /// number of unopened neighbours, as all of them have to be mines. Makes
+    /// one pass and returns number of newly placed flags. Does nothing unless
+    /// game is in progress.
+    pub fn auto_flag_obvious(&mut self) -> usize {
+        if self.status != Status::InProgress {
+            return 0;
+        }
+
+        let mut to_flag = Vec::new();
+
+        for position in self.open_positions.iter() {
+            let hidden: Vec<Position> = self
+                .neighbors(*position)
+                .into_iter()
+                .filter(|neighbour| !self.open_positions.contains(neighbour))
+                .collect();
+
+            if hidden.is_empty() || self.adjacent_mines(*position) != Ok(hidden.len() as u8) {
+                continue;
+            }
+
+            to_flag.extend(
+                hidden
+                    .into_iter()
+                    .filter(|neighbour| !self.flag_positions.contains(neighbour)),
+            );
+        }
+
+        // several positions can point to the same mine, flag it only once
+        to_flag.sort();
+        to_flag.dedup();
+
+        to_flag
+            .into_iter()
+            .filter(|position| self.flag(*position).is_ok())
+            .count()
+    }
+
+    /// Opens every position without mines around, together with their
+    /// cascades, revealing all empty areas at once. Meant to be called right
+    /// after `start`, does nothing unless game is in progress.
+    pub fn auto_open_zeros(&mut self) {
+        let zeros: Vec<Position> = self
+            .positions()
+            .filter(|position| {
+                !self.mine_positions.contains(position) && self.adjacent_mines(*position) == Ok(0)
+            })
+            .collect();
+
+        for position in zeros {
+            if self.status != Status::InProgress {
+                return;
+            }
+
+            // earlier cascade could have opened it already
+            if self.open_positions.contains(&position) || self.flag_positions.contains(&position) {
+                continue;
+            }
+
+            let _ = self.open(position);
+        }
+    }
+
+    /// Opens unflagged neighbours of opened positions whose mine count equals
+    /// number of flagged neighbours, repeating until nothing more can be
+    /// deduced. Returns number of opened positions, cascades included. Stops
+    /// once game is over, e.g. when wrong flag led to opening a mine.
+    pub fn open_all_safe(&mut self) -> usize {
+        let mut opened = 0;
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            let candidates: Vec<Position> = self.open_positions.iter().copied().collect();
+
+            for position in candidates {
+                if self.adjacent_mines(position) != self.count_flagged_neighbors(position) {
+                    continue;
+                }
+
+                let neighbours = self.neighbors(position);
+
+                for neighbour in neighbours {
+                    if self.status != Status::InProgress {
+                        return opened;
+                    }
+
+                    if self.flag_positions.contains(&neighbour)
+                        || self.open_positions.contains(&neighbour)
+                    {
+                        continue;
+                    }
+
+                    if let Ok(newly_opened) = self.open_returning(neighbour) {
+                        opened += newly_opened.len();
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        opened
+    }
+
+    /// Share of positions without mine already opened, from 0 to 100.
+    /// Field without such positions is reported as 0.
+    pub fn percent_complete(&self) -> f32 {
+        let safe_count = self.area.saturating_sub(self.mine_positions.len());
+
+        if safe_count == 0 {
+            return 0.0;
+        }
+
+        (self.open_positions.len() as f32 / safe_count as f32 * 100.0).clamp(0.0, 100.0)
+    }
+
+    /// Share of positions with flag, from 0 to 1.
+    pub fn flag_density(&self) -> f32 {
+        self.flag_positions.len() as f32 / self.area as f32
+    }
+
+    /// Mines left to flag, negative when there are more flags than mines
+    /// unless `clamp_remaining` is set.
+    pub fn remaining_mines(&self) -> isize {
+        let remaining = self.mine_positions.len() as isize - self.flag_positions.len() as isize;
+
+        if self.clamp_remaining {
+            remaining.max(0)
+        } else {
+            remaining
+        }
+    }
+
+    /// Positions neither opened nor flagged, question marks count as hidden.
+    pub fn hidden_count(&self) -> usize {
+        self.area - self.open_positions.len() - self.flag_positions.len()
+    }
+
+    /// State of position as player sees it, mines are shown only when game is over.
+    pub fn cell_at(&self, position: Position) -> Result<CellState, GameError> {
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        if self.flag_positions.contains(&position) {
+            return Ok(CellState::Flagged);
+        }
+
+        if self.question_positions.contains(&position) {
+            return Ok(CellState::Questioned);
+        }
+
+        if self.open_positions.contains(&position) {
+            return Ok(CellState::Open(self.adjacent_mines(position)?));
+        }
+
+        if self.is_over() && self.mine_positions.contains(&position) {
+            return Ok(CellState::Mine);
+        }
+
+        Ok(CellState::Hidden)
+    }
+
+    /// State of every position as `cell_at` reports it, indexed `[y][x]`:
+    /// `height` rows of `width` states each.
+    pub fn to_grid(&self) -> Vec<Vec<CellState>> {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| self.cell_at(Position(x, y)).unwrap_or(CellState::Hidden))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Field as seen by spectator, indexed `[y][x]`. Mines are never shown
+    /// unless the game is lost, so the snapshot can't leak their positions.
+    pub fn visible_state(&self) -> Vec<Vec<CellState>> {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| match self.cell_at(Position(x, y)) {
+                        Ok(CellState::Mine) if self.status == Status::Lost => CellState::Mine,
+                        Ok(CellState::Mine) | Err(_) => CellState::Hidden,
+                        Ok(state) => state,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Contents of every position in the inclusive rectangle, row by row, as
+    /// if all of them were opened: `Mine` or `Open` with mine count. Meant
+    /// for editor preview, game is not changed.
+    pub fn reveal_region(
+        &self,
+        top_left: Position,
+        bottom_right: Position,
+    ) -> Result<Vec<(Position, CellState)>, GameError> {
+        for corner in [top_left, bottom_right] {
+            if !self.is_in_bounds(&corner) {
+                return Err(GameError::OutOfBounds(corner));
+            }
+        }
+
+        if top_left.0 > bottom_right.0 || top_left.1 > bottom_right.1 {
+            return Err(GameError::InvalidRegion);
+        }
+
+        let mut region = Vec::new();
+
+        for y in top_left.1..=bottom_right.1 {
+            for x in top_left.0..=bottom_right.0 {
+                let position = Position(x, y);
+                let state = if self.mine_positions.contains(&position) {
+                    CellState::Mine
+                } else {
+                    CellState::Open(self.adjacent_mines(position)?)
+                };
+
+                region.push((position, state));
+            }
+        }
+
+        Ok(region)
+    }
+
+    /// Whether position holds mine, in any status. Meant for editors and
+    /// tests, players should rely on `cell_at`.
+    pub fn is_mine(&self, position: Position) -> Result<bool, GameError> {
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        Ok(self.mine_positions.contains(&position))
+    }
+
+    /// Whether flag on position is placed on mine, `None` for position without
+    /// flag. Only in debug builds, meant for testing solvers.
+    #[cfg(debug_assertions)]
+    pub fn is_flag_correct(&self, position: Position) -> Option<bool> {
+        if !self.flag_positions.contains(&position) {
+            return None;
+        }
+
+        Some(self.mine_positions.contains(&position))
+    }
+
+    /// Mine opened by the player, `None` unless the game is lost.
+    pub fn lost_position(&self) -> Option<Position> {
+        self.lost_at
+    }
+
+    /// Mines to show after loss. Computed from mine positions instead of
+    /// being tracked separately, so it's empty for any other status.
+    pub fn revealed_mines(&self) -> PositionSet {
+        if self.status != Status::Lost {
+            return PositionSet::new();
+        }
+
+        self.mine_positions.clone()
+    }
+
+    /// All mines in row-major order once game is won or lost, `None` before
+    /// that so the layout can't leak mid-game.
+    pub fn solution(&self) -> Option<Vec<Position>> {
+        match self.status {
+            Status::Won | Status::Lost => Some(self.mine_positions.iter().copied().collect()),
+            Status::Configuration | Status::InProgress => None,
+        }
+    }
+
+    /// Suggests hidden position least likely to hold a mine. This is a naive
+    /// estimate, not a solver: each opened neighbour gives probability of its
+    /// not flagged mines among its hidden neighbours and the worst one is
+    /// taken; positions without opened neighbours use density of not flagged
+    /// mines over all hidden positions. Ties go to the first in row-major order.
+    pub fn safest_cell(&self) -> Option<Position> {
+        let hidden: Vec<Position> = self
+            .positions()
+            .filter(|position| {
+                !self.open_positions.contains(position) && !self.flag_positions.contains(position)
+            })
+            .collect();
+
+        let unflagged_mines = self.remaining_mines().max(0) as f32;
+        let density = unflagged_mines / hidden.len().max(1) as f32;
+
+        let mut safest: Option<(Position, f32)> = None;
+
+        for position in hidden {
+            let mut estimate: Option<f32> = None;
+
+            for neighbour in self.neighbor_slice(position).iter().copied() {
+                if !self.open_positions.contains(&neighbour) {
+                    continue;
+                }
+
+                let around = self.neighbor_slice(neighbour);
+                let flags = around
+                    .iter()
+                    .filter(|cell| self.flag_positions.contains(cell))
+                    .count();
+                let unknown = around
+                    .iter()
+                    .filter(|cell| {
+                        !self.open_positions.contains(cell) && !self.flag_positions.contains(cell)
+                    })
+                    .count();
+                let mines = self.adjacent_mines(neighbour).unwrap_or(0) as usize;
+
+                let probability = mines.saturating_sub(flags) as f32 / unknown.max(1) as f32;
+
+                estimate = Some(estimate.map_or(probability, |worst| worst.max(probability)));
+            }
+
+            let estimate = estimate.unwrap_or(density);
+
+            if safest.is_none_or(|(_, lowest)| estimate < lowest) {
+                safest = Some((position, estimate));
+            }
+        }
+
+        safest.map(|(position, _)| position)
+    }
+
+    /// Checks whether the rest of the field can be cleared from current state
+    /// by logic alone. Only basic single position deductions are used (number
+    /// equals count of hidden neighbours, so they are mines; number equals
+    /// count of known mines around, so the rest is safe), so some boards
+    /// solvable by smarter reasoning are reported as needing a guess. Flags
+    /// aren't trusted, only opened positions are.
+    pub fn is_solvable_without_guessing(&self) -> bool {
+        let mut revealed = self.open_positions.clone();
+        let mut known_mines: PositionSet = PositionSet::new();
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for position in revealed.clone() {
+                let (mines, unknown): (Vec<Position>, Vec<Position>) = self
+                    .neighbors(position)
+                    .into_iter()
+                    .filter(|neighbour| !revealed.contains(neighbour))
+                    .partition(|neighbour| known_mines.contains(neighbour));
+
+                if unknown.is_empty() {
+                    continue;
+                }
+
+                let count = self.adjacent_mines(position).unwrap_or(0) as usize;
+
+                if count == mines.len() {
+                    revealed.extend(unknown);
+                    changed = true;
+                } else if count == mines.len() + unknown.len() {
+                    known_mines.extend(unknown);
+                    changed = true;
+                }
+            }
+        }
+
+        revealed.len() == self.area - self.mine_positions.len()
+    }
+
+    /// 3BV of current mine layout, minimum number of clicks needed to clear
+    /// the field: every connected region of zeros counts once (its border
+    /// opens with it), every other position without mine counts once too.
+    pub fn three_bv(&self) -> usize {
+        let is_zero = |position: &Position| {
+            !self.mine_positions.contains(position) && self.adjacent_mines(*position) == Ok(0)
+        };
+
+        let mut covered = PositionSet::new();
+        let mut clicks = 0;
+
+        for start in self.positions() {
+            if covered.contains(&start) || !is_zero(&start) {
+                continue;
+            }
+
+            clicks += 1;
+            covered.insert(start);
+
+            let mut to_visit = VecDeque::from([start]);
+
+            while let Some(current) = to_visit.pop_front() {
+                for neighbour in self.neighbor_slice(current).iter().copied() {
+                    if covered.insert(neighbour) && is_zero(&neighbour) {
+                        to_visit.push_back(neighbour);
+                    }
+                }
+            }
+        }
+
+        clicks
+            + self
+                .positions()
+                .filter(|position| {
+                    !covered.contains(position) && !self.mine_positions.contains(position)
+                })
+                .count()
+    }
+
+    /// Number of connected groups of opened positions, neighbours decided by
+    /// topology and connectivity as everywhere else.
+    pub fn open_region_count(&self) -> usize {
+        let mut visited = PositionSet::new();
+        let mut regions = 0;
+
+        for start in self.open_positions.iter() {
+            if !visited.insert(*start) {
+                continue;
+            }
+
+            regions += 1;
+
+            let mut to_visit = VecDeque::from([*start]);
+
+            while let Some(current) = to_visit.pop_front() {
+                for neighbour in self.neighbor_slice(current).iter().copied() {
+                    if self.open_positions.contains(&neighbour) && visited.insert(neighbour) {
+                        to_visit.push_back(neighbour);
+                    }
+                }
+            }
+        }
+
+        regions
+    }
+
+    /// Encodes game for saving: magic bytes `MSWP`, format version, width
+    /// and height, then status, settings and every position packed into four
+    /// bits (mine, opened, flag, question mark), stacked mines last. Numbers
+    /// are little endian, sizes take 8 bytes. Undo history and time aren't
+    /// saved.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::from(Game::SAVE_MAGIC);
+
+        data.push(Game::SAVE_VERSION);
+        data.extend_from_slice(&(self.width as u64).to_le_bytes());
+        data.extend_from_slice(&(self.height as u64).to_le_bytes());
+        data.push(match self.status {
+            Status::Configuration => 0,
+            Status::InProgress => 1,
+            Status::Won => 2,
+            Status::Lost => 3,
+        });
+
+        let settings = [
+            self.safe_first,
+            self.limit_flags,
+            self.clamp_remaining,
+            self.protect_flags,
+            self.allow_preflag,
+            self.win_by_flags,
+            self.training_mode,
+            self.editable,
+            self.wrap,
+            self.cache_neighbors,
+            self.strict_chord,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0u16, |bits, (bit, enabled)| {
+            bits | u16::from(*enabled) << bit
+        });
+
+        data.extend_from_slice(&settings.to_le_bytes());
+        data.push(match self.topology {
+            Topology::Square => 0,
+            Topology::Hex => 1,
+        });
+        data.push(match self.connectivity {
+            Connectivity::King => 0,
+            Connectivity::Orthogonal => 1,
+        });
+        data.push(self.mercies);
+        data.push(self.mercies_used);
+
+        match self.lost_at {
+            Some(position) => {
+                data.push(1);
+                data.extend_from_slice(&(position.0 as u64).to_le_bytes());
+                data.extend_from_slice(&(position.1 as u64).to_le_bytes());
+            }
+            None => data.push(0),
+        }
+
+        let mut cells = vec![0u8; self.area.div_ceil(2)];
+
+        for (index, position) in self.positions().enumerate() {
+            let cell = u8::from(self.mine_positions.contains(&position))
+                | u8::from(self.open_positions.contains(&position)) << 1
+                | u8::from(self.flag_positions.contains(&position)) << 2
+                | u8::from(self.question_positions.contains(&position)) << 3;
+
+            cells[index / 2] |= cell << (index % 2 * 4);
+        }
+
+        data.extend_from_slice(&cells);
+        data.extend_from_slice(&(self.mine_stacks.len() as u64).to_le_bytes());
+
+        for (position, count) in self.mine_stacks.iter() {
+            data.extend_from_slice(&(position.0 as u64).to_le_bytes());
+            data.extend_from_slice(&(position.1 as u64).to_le_bytes());
+            data.push(*count);
+        }
+
+        data
+    }
+
+    /// Restores game saved by `to_bytes`. Saves made by other format version
+    /// are rejected with `UnsupportedVersion`, truncated or otherwise damaged
+    /// ones with `InvalidSaveData`. Restored game in progress measures time
+    /// from now.
+    pub fn from_bytes(data: &[u8]) -> Result<Game, GameError> {
+        let mut reader = ByteReader::new(data);
+
+        if reader.bytes(Game::SAVE_MAGIC.len())? != Game::SAVE_MAGIC {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        let version = reader.u8()?;
+
+        if version != Game::SAVE_VERSION {
+            return Err(GameError::UnsupportedVersion(version));
+        }
+
+        let width = reader.usize()?;
+        let height = reader.usize()?;
+        // dimensions come from the save, so their errors mean damaged data
+        let mut game = Game::new(width, height).map_err(|_| GameError::InvalidSaveData)?;
+
+        game.status = match reader.u8()? {
+            0 => Status::Configuration,
+            1 => Status::InProgress,
+            2 => Status::Won,
+            3 => Status::Lost,
+            _ => return Err(GameError::InvalidSaveData),
+        };
+
+        let settings = reader.u16()?;
+        let enabled = |bit: u16| settings & (1 << bit) != 0;
+
+        // only eleven settings exist, the rest has to stay clear
+        if settings >> 11 != 0 {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        game.safe_first = enabled(0);
+        game.limit_flags = enabled(1);
+        game.clamp_remaining = enabled(2);
+        game.protect_flags = enabled(3);
+        game.allow_preflag = enabled(4);
+        game.win_by_flags = enabled(5);
+        game.training_mode = enabled(6);
+        game.editable = enabled(7);
+        game.wrap = enabled(8);
+        game.cache_neighbors = enabled(9);
+        game.strict_chord = enabled(10);
+
+        game.topology = match reader.u8()? {
+            0 => Topology::Square,
+            1 => Topology::Hex,
+            _ => return Err(GameError::InvalidSaveData),
+        };
+
+        Game::check_hex_wrap(game.topology, game.wrap, height)
+            .map_err(|_| GameError::InvalidSaveData)?;
+
+        game.connectivity = match reader.u8()? {
+            0 => Connectivity::King,
+            1 => Connectivity::Orthogonal,
+            _ => return Err(GameError::InvalidSaveData),
+        };
+        game.mercies = reader.u8()?;
+        game.mercies_used = reader.u8()?;
+
+        if game.mercies_used > game.mercies {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        game.lost_at = match reader.u8()? {
+            0 => None,
+            1 => Some(Position(reader.usize()?, reader.usize()?)),
+            _ => return Err(GameError::InvalidSaveData),
+        };
+
+        if game
+            .lost_at
+            .is_some_and(|position| !game.is_in_bounds(&position))
+        {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        if game.lost_at.is_some() != (game.status == Status::Lost) {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        let cells = reader.bytes(game.area.div_ceil(2))?;
+
+        // odd number of positions leaves high half of the last byte unused
+        if game.area % 2 == 1 && cells[cells.len() - 1] >> 4 != 0 {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        for index in 0..game.area {
+            let position = Position(index % width, index / width);
+            let cell = cells[index / 2] >> (index % 2 * 4);
+            let (mined, opened) = (cell & 1 != 0, cell & 2 != 0);
+            let (flagged, questioned) = (cell & 4 != 0, cell & 8 != 0);
+
+            if (opened && (flagged || questioned))
+                || (flagged && questioned)
+                || (opened && mined && game.status != Status::Lost)
+            {
+                return Err(GameError::InvalidSaveData);
+            }
+
+            if mined {
+                game.mine_positions.insert(position);
+            }
+            if opened {
+                game.open_positions.insert(position);
+            }
+            if flagged {
+                game.flag_positions.insert(position);
+            }
+            if questioned {
+                game.question_positions.insert(position);
+            }
+        }
+
+        if game.mine_positions.len() >= game.area {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        if game
+            .lost_at
+            .is_some_and(|position| !game.mine_positions.contains(&position))
+        {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        for _ in 0..reader.usize()? {
+            let position = Position(reader.usize()?, reader.usize()?);
+            let count = reader.u8()?;
+
+            // stacks are written sorted, each on a mine
+            let is_sorted = game
+                .mine_stacks
+                .last()
+                .is_none_or(|(previous, _)| *previous < position);
+
+            if !is_sorted
+                || !game.mine_positions.contains(&position)
+                || !(2..=Game::MAX_STACKED_MINES).contains(&count)
+            {
+                return Err(GameError::InvalidSaveData);
+            }
+
+            game.mine_stacks.push((position, count));
+        }
+
+        if !reader.is_empty() {
+            return Err(GameError::InvalidSaveData);
+        }
+
+        if game.status == Status::InProgress {
+            game.timer.start();
+        }
+
+        Ok(game)
+    }
+
+    /// Hash of dimensions and mine positions, stable between runs
+    /// (FNV-1a over mines in row-major order).
+    pub fn board_hash(&self) -> u64 {
+        let mut values = vec![self.width as u64, self.height as u64];
+        for mine in self.mine_positions.iter() {
+            values.push(mine.0 as u64);
+            values.push(mine.1 as u64);
+        }
+        for (stacked, count) in self.mine_stacks.iter() {
+            values.push(stacked.0 as u64);
+            values.push(stacked.1 as u64);
+            values.push(u64::from(*count));
+        }
+
+        let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+        for byte in values.iter().flat_map(|value| value.to_le_bytes()) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01B3);
+        }
+
+        hash
+    }
+
+    /// Renders field as text grid, every row (including the last one)
+    /// ends with `\n`. Hidden positions are `.`, flags `F`, question marks
+    /// `?`, mines of finished game `*`, opened positions show mine count,
+    /// space for zero or `+` for counts above 9.
+    pub fn render(&self) -> String {
+        let mut board = String::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let symbol = match self.cell_at(Position(x, y)) {
+                    Ok(CellState::Flagged) => 'F',
+                    Ok(CellState::Questioned) => '?',
+                    Ok(CellState::Mine) => '*',
+                    Ok(CellState::Open(0)) => ' ',
+                    Ok(CellState::Open(count @ 1..=9)) => char::from(b'0' + count),
+                    Ok(CellState::Open(_)) => '+',
+                    _ => '.',
+                };
+
+                board.push(symbol);
+            }
+
+            board.push('\n');
+        }
+
+        board
+    }
+
+    /// Renders field as binary PPM (P6) image, every position drawn as
+    /// `cell_px` by `cell_px` square. Hidden positions are grey, flags red,
+    /// question marks yellow, mines black and opened positions get lighter
+    /// the fewer mines are around them. Fails with `AreaOverflow` when size
+    /// of the image doesn't fit in `usize`.
+    pub fn to_ppm(&self, cell_px: usize) -> Result<Vec<u8>, GameError> {
+        let image_width = self.width.checked_mul(cell_px);
+        let image_height = self.height.checked_mul(cell_px);
+
+        // three bytes per pixel
+        image_width
+            .zip(image_height)
+            .and_then(|(width, height)| width.checked_mul(height))
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or(GameError::AreaOverflow)?;
+
+        let mut image = Vec::new();
+        let header = format!(
+            "P6\n{} {}\n255\n",
+            self.width * cell_px,
+            self.height * cell_px
+        );
+
+        image.extend_from_slice(header.as_bytes());
+
+        for y in 0..self.height {
+            let row: Vec<[u8; 3]> = (0..self.width)
+                .map(|x| match self.cell_at(Position(x, y)) {
+                    Ok(CellState::Flagged) => [220, 40, 40],
+                    Ok(CellState::Questioned) => [230, 200, 40],
+                    Ok(CellState::Mine) => [0, 0, 0],
+                    Ok(CellState::Open(count)) => {
+                        let shade = 255 - count.min(8) * 20;
+                        [shade, shade, 255]
+                    }
+                    _ => [128, 128, 128],
+                })
+                .collect();
+
+            for _ in 0..cell_px {
+                for color in row.iter() {
+                    for _ in 0..cell_px {
+                        image.extend_from_slice(color);
+                    }
+                }
+            }
+        }
+
+        Ok(image)
+    }
+
+    fn wrap_coordinate(coordinate: usize, dif: isize, size: usize) -> usize {
+        match dif {
+            -1 if coordinate == 0 => size - 1,
+            -1 => coordinate - 1,
+            1 if coordinate + 1 >= size => 0,
+            1 => coordinate + 1,
+            _ => coordinate,
+        }
+    }
+
+    /// Positions around given one which are inside the field. On wrapping
+    /// field offsets past an edge continue from the opposite one, positions
+    /// are listed once even if several offsets lead to them.
+    pub fn neighbors(&self, position: Position) -> Vec<Position> {
+        self.neighbor_slice(position).into_owned()
+    }
+
+    // Same as `neighbors`, but borrows from the cache when it is enabled
+    // instead of copying, for lookups repeated over the whole field.
+    fn neighbor_slice(&self, position: Position) -> Cow<'_, [Position]> {
+        if self.cache_neighbors && self.is_in_bounds(&position) {
+            let cached =
+                self.neighbor_cache
+                    .get_or_build(position.1 * self.width + position.0, || {
+                        self.positions()
+                            .map(|position| self.compute_neighbors(position))
+                            .collect()
+                    });
+
+            if let Some(cached) = cached {
+                return Cow::Borrowed(cached);
+            }
+        }
+
+        Cow::Owned(self.compute_neighbors(position))
+    }
+
+    fn compute_neighbors(&self, position: Position) -> Vec<Position> {
+        self.neighbors_with_offset(position)
+            .into_iter()
+            .map(|(neighbour, _)| neighbour)
+            .collect()
+    }
+
+    /// Same as `neighbors`, but every neighbour is paired with the offset
+    /// leading to it. On wrapping field offset can point past an edge.
+    pub fn neighbors_with_offset(&self, position: Position) -> Vec<(Position, (isize, isize))> {
+        let mut neighbours: Vec<(Position, (isize, isize))> = Vec::new();
+
+        let orthogonal =
+            self.connectivity == Connectivity::Orthogonal && self.topology == Topology::Square;
+
+        for (x_dif, y_dif) in self.topology.offsets(position.1) {
+            if orthogonal && *x_dif != 0 && *y_dif != 0 {
+                continue;
+            }
+
+            if self.wrap {
+                let neighbour = Position(
+                    Game::wrap_coordinate(position.0, *x_dif, self.width),
+                    Game::wrap_coordinate(position.1, *y_dif, self.height),
+                );
+
+                // small fields wrap onto the same positions several times
+                if neighbour != position
+                    && self.is_in_bounds(&neighbour)
+                    && !neighbours.iter().any(|(listed, _)| *listed == neighbour)
+                {
+                    neighbours.push((neighbour, (*x_dif, *y_dif)));
+                }
+
+                continue;
+            }
+
+            // out of bounds neighbours are skipped, they are not part of the field
+            if let Ok(neighbour) = position.get_relative(*x_dif, *y_dif) {
+                if self.is_in_bounds(&neighbour) {
+                    neighbours.push((neighbour, (*x_dif, *y_dif)));
+                }
+            }
+        }
+
+        neighbours
+    }
+
+    /// Neighbours paired with their state as player sees it.
+    pub fn neighbors_with_state(&self, position: Position) -> Vec<(Position, CellState)> {
+        self.neighbors(position)
+            .into_iter()
+            .filter_map(|neighbour| Some((neighbour, self.cell_at(neighbour).ok()?)))
+            .collect()
+    }
+
+    pub fn adjacent_mines(&self, position: Position) -> Result<u8, GameError> {
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        let mut mine_proximity_counter: u8 = 0;
+
+        for neighbour in self.neighbor_slice(position).iter().copied() {
+            mine_proximity_counter += self.mines_at(neighbour);
+        }
+
+        Ok(mine_proximity_counter)
+    }
+
+    /// Mine counts of all positions at once, indexed `[y][x]`, same values as
+    /// `adjacent_mines` gives. Spreads every mine to its neighbours instead
+    /// of scanning neighbours of every position.
+    pub fn adjacency_grid(&self) -> Vec<Vec<u8>> {
+        let mut grid = vec![vec![0; self.width]; self.height];
+
+        for mine in self.mine_positions.iter() {
+            let count = self.mines_at(*mine);
+
+            for neighbour in self.neighbor_slice(*mine).iter().copied() {
+                grid[neighbour.1][neighbour.0] += count;
+            }
+        }
+
+        grid
+    }
+
+    /// Number of flags around position, which chording compares with its mine
+    /// count. Flag on stacked mines counts as all of them, so stacks can be
+    /// chorded around like single mines.
+    pub fn count_flagged_neighbors(&self, position: Position) -> Result<u8, GameError> {
+        if !self.is_in_bounds(&position) {
+            return Err(GameError::OutOfBounds(position));
+        }
+
+        let flag_count = self
+            .neighbor_slice(position)
+            .iter()
+            .filter(|neighbour| self.flag_positions.contains(neighbour))
+            .map(|neighbour| self.mines_at(*neighbour).max(1))
+            .sum();
+
+        Ok(flag_count)
+    }
+
+    // Places mines on random positions outside of `excluded`, caller makes
+    // sure enough of them are left.
+    fn scatter_mines(&mut self, mine_count: usize, seed: u64, excluded: &[Position]) {
+        let mut generator = XorShift::new(seed);
+
+        while self.mine_positions.len() < mine_count {
+            let index = generator.below(self.area);
+            let position = Position(index % self.width, index / self.width);
+
+            if !excluded.contains(&position) {
+                self.mine_positions.insert(position);
+            }
+        }
+    }
+
+    // Opens position and every cell reachable from it through zero cells.
+    // Cells with mines around are opened too, but the cascade doesn't go
+    // further from them. Returns opened positions and positions which lost
+    // question mark on the way. Uses queue instead of recursion, so large
+    // empty fields can't overflow the stack.
+    fn cascade(&mut self, position: Position) -> (Vec<Position>, Vec<Position>) {
+        let mut opened = vec![position];
+        let mut questioned = Vec::new();
+        let mut to_visit = VecDeque::from([position]);
+
+        self.open_positions.insert(position);
+        self.notify_open(position);
+
+        while let Some(current) = to_visit.pop_front() {
+            if self.adjacent_mines(current) != Ok(0) {
+                continue;
+            }
+
+            for neighbour in self.neighbors(current) {
+                if self.open_positions.contains(&neighbour)
+                    || self.flag_positions.contains(&neighbour)
+                    || self.mine_positions.contains(&neighbour)
+                {
+                    continue;
+                }
+
+                if self.question_positions.remove(&neighbour) {
+                    questioned.push(neighbour);
+                }
+
+                self.open_positions.insert(neighbour);
+                self.notify_open(neighbour);
+                opened.push(neighbour);
+                to_visit.push_back(neighbour);
+            }
+        }
+
+        (opened, questioned)
+    }
+
+    fn notify_open(&mut self, position: Position) {
+        if self.on_open.is_set() {
+            let count = self.adjacent_mines(position).unwrap_or(0);
+
+            self.on_open.call(position, count);
+        }
+    }
+}
+
+/// Same grid as `Game::render`.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+#[cfg(test)]
+mod game_new {
+    use super::*;
+
+    #[test]
+    fn create_new_game() {
+        let game = Game::new(100, 100).expect("game created");
+
+        assert_eq!(game.width, 100);
+        assert_eq!(game.height, 100);
+        assert_eq!(game.mine_positions.len(), 0);
+        assert_eq!(game.open_positions.len(), 0);
+        assert_eq!(game.flag_positions.len(), 0);
+        assert_eq!(game.status, Status::Configuration);
+    }
+
+    #[test]
+    fn zero_area() {
+        assert!(matches!(Game::new(0, 1), Err(GameError::ZeroFieldArea)));
+    }
+
+    #[test]
+    fn area_overflow() {
+        assert_eq!(Game::new(usize::MAX, 2), Err(GameError::AreaOverflow));
+    }
+}
+
+#[cfg(test)]
+mod game_new_random {
+    use super::*;
+
+    #[test]
+    fn create_random_game() {
+        let game = Game::new_random(10, 10, 15, 1).expect("game created");
+
+        assert_eq!(game.mine_positions.len(), 15);
+        assert_eq!(game.status, Status::Configuration);
+        assert!(game
+            .mine_positions
+            .iter()
+            .all(|position| game.is_in_bounds(position)));
+    }
+
+    #[test]
+    fn same_seed() {
+        let first = Game::new_random(16, 16, 40, 12345).expect("game created");
+        let second = Game::new_random(16, 16, 40, 12345).expect("game created");
+
+        assert_eq!(first.mine_positions, second.mine_positions);
+    }
+
+    #[test]
+    fn different_seed() {
+        let first = Game::new_random(16, 16, 40, 1).expect("game created");
+        let second = Game::new_random(16, 16, 40, 2).expect("game created");
+
+        assert_ne!(first.mine_positions, second.mine_positions);
+    }
+
+    #[test]
+    fn too_many_mines() {
+        assert!(matches!(
+            Game::new_random(3, 3, 9, 1),
+            Err(GameError::TooManyMines)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod game_from_seed_with_first_click_safe {
+    use super::*;
+
+    #[test]
+    fn first_click_and_neighbors_safe() {
+        for seed in 0..20 {
+            let game = Game::from_seed_with_first_click_safe(9, 9, 70, seed, Position(4, 4), true)
+                .expect("game created");
+
+            assert_eq!(game.mine_positions.len(), 70);
+            assert_eq!(game.status, Status::InProgress);
+            assert!(game.open_positions.contains(&Position(4, 4)));
+            assert!(!game.mine_positions.contains(&Position(4, 4)));
+            for neighbour in game.neighbors(Position(4, 4)) {
+                assert!(!game.mine_positions.contains(&neighbour));
+            }
+        }
+    }
+
+    #[test]
+    fn only_first_click_safe() {
+        let game = Game::from_seed_with_first_click_safe(3, 3, 8, 1, Position(0, 0), false)
+            .expect("game created");
+
+        assert!(!game.mine_positions.contains(&Position(0, 0)));
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn too_many_mines() {
+        assert_eq!(
+            Game::from_seed_with_first_click_safe(3, 3, 1, 1, Position(1, 1), true),
+            Err(GameError::TooManyMines)
+        );
+    }
+
+    #[test]
+    fn first_click_out_of_bounds() {
+        assert_eq!(
+            Game::from_seed_with_first_click_safe(3, 3, 1, 1, Position(3, 0), false),
+            Err(GameError::OutOfBounds(Position(3, 0)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_ideal_mine_count {
+    use super::*;
+
+    #[test]
+    fn typical_densities() {
+        assert_eq!(Game::ideal_mine_count(16, 16, 0.15), 38);
+        assert_eq!(Game::ideal_mine_count(9, 9, 0.125), 10);
+        assert_eq!(Game::ideal_mine_count(10, 10, 0.0), 0);
+    }
+
+    #[test]
+    fn keeps_one_safe_position() {
+        assert_eq!(Game::ideal_mine_count(4, 4, 0.999), 15);
+        assert_eq!(Game::ideal_mine_count(4, 4, 2.0), 15);
+        assert!(Game::new_random(4, 4, Game::ideal_mine_count(4, 4, 0.999), 1).is_ok());
+    }
+
+    #[test]
+    fn negative_density() {
+        assert_eq!(Game::ideal_mine_count(4, 4, -0.5), 0);
+    }
+
+    #[test]
+    fn huge_area() {
+        assert_eq!(Game::ideal_mine_count(usize::MAX, 2, 0.0), 0);
+        assert!(Game::ideal_mine_count(usize::MAX, 2, 0.1) > 0);
+        assert_eq!(Game::ideal_mine_count(usize::MAX, 2, 2.0), usize::MAX - 1);
+    }
+}
+
+#[cfg(test)]
+mod game_from_layout {
+    use super::*;
+
+    #[test]
+    fn parse_layout() {
+        let game = Game::from_layout("*..\n...\n..*\n").expect("game created");
+
+        assert_eq!((game.width, game.height), (3, 3));
+        assert_eq!(game.mine_positions.len(), 2);
+        assert!(game.mine_positions.contains(&Position(0, 0)));
+        assert!(game.mine_positions.contains(&Position(2, 2)));
+        assert_eq!(game.status, Status::Configuration);
+    }
+
+    #[test]
+    fn ragged_layout() {
+        assert!(matches!(
+            Game::from_layout("*..\n..\n..*"),
+            Err(GameError::RaggedLayout)
+        ));
+    }
+
+    #[test]
+    fn unknown_symbol() {
+        assert!(matches!(
+            Game::from_layout("*.\n.x"),
+            Err(GameError::UnknownLayoutSymbol('x'))
+        ));
+    }
+
+    #[test]
+    fn empty_layout() {
+        assert!(matches!(
+            Game::from_layout(""),
+            Err(GameError::ZeroFieldArea)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod game_generate_in_3bv_range {
+    use super::*;
+
+    #[test]
+    fn within_range() {
+        let game = Game::generate_in_3bv_range(9, 9, 10, 10, 40, 1, 100).expect("game created");
+
+        assert!((10..=40).contains(&game.three_bv()));
+        assert_eq!(game.mine_positions.len(), 10);
+    }
+
+    #[test]
+    fn out_of_reach() {
+        // 4 safe positions can't take more than 4 clicks
+        assert_eq!(
+            Game::generate_in_3bv_range(3, 3, 5, 5, 9, 1, 20),
+            Err(GameError::GenerationFailed)
+        );
+    }
+
+    #[test]
+    fn invalid_board() {
+        assert_eq!(
+            Game::generate_in_3bv_range(3, 3, 9, 1, 9, 1, 20),
+            Err(GameError::TooManyMines)
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_presets {
+    use super::*;
+
+    #[test]
+    fn beginner() {
+        let game = Game::beginner(1).expect("game created");
+
+        assert_eq!((game.width, game.height), (9, 9));
+        assert_eq!(game.mine_positions.len(), 10);
+        assert_eq!(game.status, Status::Configuration);
+    }
+
+    #[test]
+    fn intermediate() {
+        let game = Game::intermediate(1).expect("game created");
+
+        assert_eq!((game.width, game.height), (16, 16));
+        assert_eq!(game.mine_positions.len(), 40);
+        assert_eq!(game.status, Status::Configuration);
+    }
+
+    #[test]
+    fn expert() {
+        let game = Game::expert(1).expect("game created");
+
+        assert_eq!((game.width, game.height), (30, 16));
+        assert_eq!(game.mine_positions.len(), 99);
+        assert_eq!(game.status, Status::Configuration);
+    }
+}
+
+#[cfg(test)]
+mod game_clone {
+    use super::*;
+
+    #[test]
+    fn clone_is_independent() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.open(Position(1, 2)).expect("Position opened");
+
+        let mut snapshot = game.clone();
+        assert_eq!(snapshot, game);
+
+        snapshot.open(Position(2, 2)).expect("Position opened");
+
+        assert_ne!(snapshot, game);
+        assert!(!game.open_positions.contains(&Position(2, 2)));
+        assert_eq!(game.open_positions.len(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod game_serde {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let mut game = Game::new(5, 4).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(3, 2)).expect("Set mine");
+
+        let json = serde_json::to_string(&game).expect("game serialized");
+        let restored: Game = serde_json::from_str(&json).expect("game deserialized");
+
+        assert_eq!(restored, game);
+    }
+}
+
+#[cfg(test)]
+mod game_status {
+    use super::*;
+
+    #[test]
+    fn status_through_game() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        assert_eq!(game.status(), Status::Configuration);
+
+        game.start().expect("Game started");
+        assert_eq!(game.status(), Status::InProgress);
+
+        game.open(Position(1, 0)).expect("Position opened");
+        assert_eq!(game.status(), Status::Won);
+    }
+
+    #[test]
+    fn predicates() {
+        let mut game = Game::from_layout("*..").expect("game created");
+
+        assert!(!game.is_won() && !game.is_lost() && !game.is_over());
+
+        game.start().expect("Game started");
+        assert!(!game.is_won() && !game.is_lost() && !game.is_over());
+
+        let mut lost = game.clone();
+
+        game.open(Position(2, 0)).expect("Position opened");
+        assert!(game.is_won() && !game.is_lost() && game.is_over());
+
+        lost.open(Position(0, 0)).expect("Position opened");
+        assert!(!lost.is_won() && lost.is_lost() && lost.is_over());
+    }
+}
+
+#[cfg(test)]
+mod game_mine_count {
+    use super::*;
+
+    #[test]
+    fn count_mines() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        assert_eq!(game.mine_count(), 0);
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(2, 2)).expect("Set mine");
+
+        assert_eq!(game.mine_count(), 2);
+    }
+}
+
+#[cfg(test)]
+mod game_cells {
+    use super::*;
+
+    #[test]
+    fn open_and_flagged() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine(Position(2, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(4, 4)).expect("Position flagged");
+        game.open(Position(1, 0)).expect("Position opened");
+
+        let open: Vec<Position> = game.open_cells().collect();
+        let flagged: Vec<Position> = game.flagged_cells().collect();
+
+        assert_eq!(open, vec![Position(1, 0)]);
+        assert_eq!(flagged, vec![Position(0, 0), Position(4, 4)]);
+    }
+
+    #[test]
+    fn deterministic_order() {
+        let play = || {
+            let mut game = Game::new_random(16, 16, 40, 7).expect("game created");
+
+            game.set_safe_first(true).expect("Safe first set");
+            game.start().expect("Game started");
+            game.open(Position(8, 8)).expect("Position opened");
+            game.open_cells().collect::<Vec<Position>>()
+        };
+
+        let open = play();
+        let mut sorted = open.clone();
+        sorted.sort();
+
+        assert_eq!(open, play());
+        assert_eq!(open, sorted);
+    }
+}
+
+#[cfg(test)]
+mod game_positions {
+    use super::*;
+
+    #[test]
+    fn row_major_order() {
+        let game = Game::new(3, 2).expect("game created");
+
+        assert_eq!(
+            game.positions().collect::<Vec<Position>>(),
+            vec![
+                Position(0, 0),
+                Position(1, 0),
+                Position(2, 0),
+                Position(0, 1),
+                Position(1, 1),
+                Position(2, 1)
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_is_in_bounds {
+    use super::*;
+
+    #[test]
+    fn in_bounds() {
+        let game = Game::new(10, 10).expect("game created");
+
+        assert!(game.is_in_bounds(&Position(1, 1)));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let game = Game::new(10, 10).expect("game created");
+
+        assert!(!game.is_in_bounds(&Position(100, 1)));
+    }
+
+    #[test]
+    fn zero_width() {
+        let mut game = Game::new(1, 1).expect("game created");
+
+        game.width = 0;
+
+        assert!(!game.is_in_bounds(&Position(0, 0)));
+    }
+}
+
+#[cfg(test)]
+mod game_relative {
+    use super::*;
+
+    #[test]
+    fn inside_field() {
+        let game = Game::new(5, 3).expect("game created");
+
+        assert_eq!(game.relative(Position(2, 1), 2, 1), Ok(Position(4, 2)));
+    }
+
+    #[test]
+    fn past_right_edge() {
+        let game = Game::new(5, 3).expect("game created");
+
+        assert_eq!(Position(4, 1).get_relative(1, 0), Ok(Position(5, 1)));
+        assert_eq!(
+            game.relative(Position(4, 1), 1, 0),
+            Err(GameError::OutOfBounds(Position(5, 1)))
+        );
+    }
+
+    #[test]
+    fn before_left_edge() {
+        let game = Game::new(5, 3).expect("game created");
+
+        assert_eq!(
+            game.relative(Position(0, 1), -1, 0),
+            Err(GameError::Underflow(Position(0, 1)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_mine {
+    use super::*;
+
+    #[test]
+    fn set_mine_in_fresh_game() {
+        let mut game = Game::new(100, 100).expect("game created");
+
+        let mine_position = Position(1, 1);
+
+        game.mine(mine_position).expect("Set mine");
+
+        assert!(game.mine_positions.contains(&mine_position));
+    }
+
+    #[test]
+    fn set_mine_in_progress_game() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.mine(Position(1, 1)),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+
+    #[test]
+    fn set_mine_twice() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let mine_position = Position(1, 1);
+
+        game.mine(mine_position).expect("Set mine");
+        assert_eq!(
+            game.mine(mine_position),
+            Err(GameError::AlreadyMined(mine_position))
+        );
+    }
+
+    #[test]
+    fn fill_whole_board() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+
+        assert_eq!(game.mine(Position(1, 0)), Err(GameError::BoardFull));
+        assert_eq!(game.mine_positions.len(), 1);
+    }
+
+    #[test]
+    fn set_mine_out_of_bounds() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        assert_eq!(
+            game.mine(Position(20, 5)),
+            Err(GameError::OutOfBounds(Position(20, 5)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_ensure_mine {
+    use super::*;
+
+    #[test]
+    fn first_placement() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(game.ensure_mine(Position(1, 1)), Ok(true));
+        assert!(game.mine_positions.contains(&Position(1, 1)));
+    }
+
+    #[test]
+    fn repeat_placement() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine_with_count(Position(1, 1), 2).expect("Set mine");
+
+        assert_eq!(game.ensure_mine(Position(1, 1)), Ok(false));
+        assert_eq!(game.mines_at(Position(1, 1)), 2);
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(
+            game.ensure_mine(Position(1, 3)),
+            Err(GameError::OutOfBounds(Position(1, 3)))
+        );
+    }
+
+    #[test]
+    fn after_start() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.ensure_mine(Position(1, 1)),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_unmine {
+    use super::*;
+
+    #[test]
+    fn remove_mine() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.unmine(Position(1, 1)).expect("Mine removed");
+
+        assert!(game.mine_positions.is_empty());
+        assert_eq!(game.adjacent_mines(Position(0, 0)), Ok(0));
+    }
+
+    #[test]
+    fn remove_stack() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine_with_count(Position(1, 1), 3).expect("Set mine");
+        game.unmine(Position(1, 1)).expect("Mine removed");
+
+        assert!(game.mine_stacks.is_empty());
+        assert_eq!(game.mines_at(Position(1, 1)), 0);
+    }
+
+    #[test]
+    fn not_mined() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(
+            game.unmine(Position(1, 1)),
+            Err(GameError::NotMined(Position(1, 1)))
+        );
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(
+            game.unmine(Position(3, 1)),
+            Err(GameError::OutOfBounds(Position(3, 1)))
+        );
+    }
+
+    #[test]
+    fn remove_after_start() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.unmine(Position(1, 1)),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+        assert!(game.mine_positions.contains(&Position(1, 1)));
+    }
+}
+
+#[cfg(test)]
+mod game_mine_all {
+    use super::*;
+
+    #[test]
+    fn place_batch() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine_all(&[Position(0, 0), Position(1, 1), Position(2, 2)])
+            .expect("Set mines");
+
+        assert_eq!(game.mine_positions.len(), 3);
+    }
+
+    #[test]
+    fn rollback_on_failure() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(4, 4)).expect("Set mine");
+
+        assert_eq!(
+            game.mine_all(&[Position(0, 0), Position(1, 1), Position(9, 9)]),
+            Err(GameError::OutOfBounds(Position(9, 9)))
+        );
+        assert_eq!(game.mine_positions.len(), 1);
+        assert!(game.mine_positions.contains(&Position(4, 4)));
+    }
+
+    #[test]
+    fn duplicate_in_batch() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        assert_eq!(
+            game.mine_all(&[Position(0, 0), Position(0, 0)]),
+            Err(GameError::AlreadyMined(Position(0, 0)))
+        );
+        assert!(game.mine_positions.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod game_mine_with_count {
+    use super::*;
+
+    #[test]
+    fn neighbors_count_stack() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine_with_count(Position(0, 0), 3).expect("Set mine");
+        game.mine(Position(2, 0)).expect("Set mine");
+
+        assert_eq!(game.mines_at(Position(0, 0)), 3);
+        assert_eq!(game.adjacent_mines(Position(1, 1)), Ok(4));
+        assert_eq!(game.adjacent_mines(Position(0, 1)), Ok(3));
+    }
+
+    #[test]
+    fn single_mine_by_default() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine_with_count(Position(2, 2), 1).expect("Set mine");
+
+        assert_eq!(game.mines_at(Position(0, 0)), 1);
+        assert_eq!(game.mines_at(Position(2, 2)), 1);
+        assert_eq!(game.mines_at(Position(1, 1)), 0);
+        assert!(game.mine_stacks.is_empty());
+    }
+
+    #[test]
+    fn invalid_count() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(
+            game.mine_with_count(Position(0, 0), 0),
+            Err(GameError::InvalidMineCount(0))
+        );
+        assert_eq!(
+            game.mine_with_count(Position(0, 0), Game::MAX_STACKED_MINES + 1),
+            Err(GameError::InvalidMineCount(32))
+        );
+        assert!(game.mine_positions.is_empty());
+    }
+
+    #[test]
+    fn full_neighbourhood() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        for position in game.neighbors(Position(1, 1)) {
+            game.mine_with_count(position, Game::MAX_STACKED_MINES)
+                .expect("Set mine");
+        }
+
+        assert_eq!(game.adjacent_mines(Position(1, 1)), Ok(248));
+    }
+
+    #[test]
+    fn relocated_stack() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine_with_count(Position(0, 0), 2).expect("Set mine");
+        game.set_safe_first(true).expect("Safe first set");
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.mines_at(Position(0, 0)), 0);
+        assert_eq!(game.mines_at(Position(1, 0)), 2);
+
+        game.undo().expect("Move undone");
+
+        assert_eq!(game.mines_at(Position(0, 0)), 2);
+        assert_eq!(game.mines_at(Position(1, 0)), 0);
+    }
+
+    #[test]
+    fn chord_next_to_stack() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine_with_count(Position(0, 0), 2).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+        game.flag(Position(0, 0)).expect("Position flagged");
+
+        assert_eq!(game.adjacent_mines(Position(1, 1)), Ok(2));
+
+        game.chord(Position(1, 1)).expect("Position chorded");
+
+        assert_eq!(game.status, Status::Won);
+    }
+}
+
+#[cfg(test)]
+mod game_set_safe_first {
+    use super::*;
+
+    #[test]
+    fn disabled_by_default() {
+        let game = Game::new(10, 10).expect("game created");
+
+        assert!(!game.safe_first);
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_safe_first(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+
+    #[test]
+    fn first_open_relocates_mine() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let mine_position = Position(0, 0);
+
+        game.mine(mine_position).expect("Set mine");
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.set_safe_first(true).expect("Safe first set");
+        game.start().expect("Game started");
+
+        game.open(mine_position).expect("Position opened");
+
+        assert_eq!(game.status, Status::InProgress);
+        assert!(game.open_positions.contains(&mine_position));
+        assert!(!game.mine_positions.contains(&mine_position));
+        assert!(game.mine_positions.contains(&Position(2, 0)));
+        assert_eq!(game.mine_positions.len(), 2);
+    }
+
+    #[test]
+    fn next_opens_behave_normally() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let mine_position = Position(5, 5);
+
+        game.mine(mine_position).expect("Set mine");
+        game.mine(Position(0, 1)).expect("Set mine");
+        game.set_safe_first(true).expect("Safe first set");
+        game.start().expect("Game started");
+
+        game.open(Position(0, 0)).expect("Position opened");
+        game.open(mine_position).expect("Position opened");
+
+        assert_eq!(game.status, Status::Lost);
+        assert!(game.mine_positions.contains(&mine_position));
+    }
+}
+
+#[cfg(test)]
+mod game_set_editable {
+    use super::*;
+
+    #[test]
+    fn mine_in_progress() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.set_editable(true).expect("Editable set");
+        game.start().expect("Game started");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        assert!(game.mine_positions.contains(&Position(1, 1)));
+
+        assert_eq!(
+            game.mine(Position(1, 1)),
+            Err(GameError::AlreadyMined(Position(1, 1)))
+        );
+        assert_eq!(
+            game.mine(Position(5, 1)),
+            Err(GameError::OutOfBounds(Position(5, 1)))
+        );
+    }
+
+    #[test]
+    fn mine_opened_position() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.set_editable(true).expect("Editable set");
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+
+        assert_eq!(
+            game.mine(Position(1, 1)),
+            Err(GameError::AlreadyOpened(Position(1, 1)))
+        );
+    }
+
+    #[test]
+    fn mine_last_safe_position() {
+        let mut game = Game::from_layout("*..").expect("game created");
+
+        game.set_editable(true).expect("Editable set");
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+        game.mine(Position(2, 0)).expect("Set mine");
+
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn not_editable() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.set_editable(false).expect("Editable set");
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.mine(Position(1, 1)),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_wrap {
+    use super::*;
+
+    #[test]
+    fn corner_neighbors() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        assert_eq!(game.neighbors(Position(0, 0)).len(), 3);
+
+        game.set_wrap(true).expect("Wrap set");
+
+        let neighbors = game.neighbors(Position(0, 0));
+
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.contains(&Position(4, 4)));
+        assert!(neighbors.contains(&Position(4, 0)));
+        assert!(neighbors.contains(&Position(0, 4)));
+    }
+
+    #[test]
+    fn small_field() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.set_wrap(true).expect("Wrap set");
+
+        assert_eq!(game.neighbors(Position(0, 0)), vec![Position(1, 0)]);
+    }
+
+    #[test]
+    fn adjacent_across_edge() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(4, 4)).expect("Set mine");
+        game.set_wrap(true).expect("Wrap set");
+
+        assert_eq!(game.adjacent_mines(Position(0, 0)), Ok(1));
+    }
+
+    #[test]
+    fn cascade_across_edge() {
+        let mut game = Game::new(5, 1).expect("game created");
+
+        game.mine(Position(2, 0)).expect("Set mine");
+        game.set_wrap(true).expect("Wrap set");
+        game.start().expect("Game started");
+
+        game.open(Position(4, 0)).expect("Position opened");
+
+        assert!(game.open_positions.contains(&Position(0, 0)));
+        assert!(game.open_positions.contains(&Position(1, 0)));
+        assert!(game.open_positions.contains(&Position(3, 0)));
+    }
+
+    #[test]
+    fn hex_odd_height() {
+        let mut game = Game::new(6, 7).expect("game created");
+
+        game.set_topology(Topology::Hex).expect("Topology set");
+
+        assert_eq!(game.set_wrap(true), Err(GameError::HexWrapOddHeight));
+        assert!(!game.wrap);
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_wrap(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_resize {
+    use super::*;
+
+    #[test]
+    fn grow_field() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.resize(5, 4).expect("Field resized");
+
+        assert_eq!((game.width, game.height), (5, 4));
+        assert_eq!(game.mine_positions, PositionSet::from([Position(2, 2)]));
+    }
+
+    #[test]
+    fn shrink_field() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine(Position(4, 1)).expect("Set mine");
+        game.mine(Position(1, 3)).expect("Set mine");
+        game.resize(3, 3).expect("Field resized");
+
+        assert_eq!(game.mine_positions, PositionSet::from([Position(0, 0)]));
+        assert_eq!(game.neighbors(Position(2, 2)).len(), 3);
+    }
+
+    #[test]
+    fn no_position_left() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+
+        assert_eq!(game.resize(1, 1), Err(GameError::BoardFull));
+        assert_eq!(game.width, 2);
+    }
+
+    #[test]
+    fn zero_dimension() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(game.resize(0, 3), Err(GameError::ZeroFieldArea));
+    }
+
+    #[test]
+    fn area_overflow() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(game.resize(2, usize::MAX), Err(GameError::AreaOverflow));
+        assert_eq!(game.area, 9);
+    }
+
+    #[test]
+    fn wrapped_hex_odd_height() {
+        let mut game = Game::new(4, 4).expect("game created");
+
+        game.set_topology(Topology::Hex).expect("Topology set");
+        game.set_wrap(true).expect("Wrap set");
+
+        assert_eq!(game.resize(4, 5), Err(GameError::HexWrapOddHeight));
+        assert_eq!((game.width, game.height), (4, 4));
+    }
+
+    #[test]
+    fn resize_in_progress_game() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.resize(4, 4),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_on_open {
+    use super::*;
+    use std::panic::{RefUnwindSafe, UnwindSafe};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn sees_cascade() {
+        let mut game = Game::from_layout("*...\n....\n....").expect("game created");
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+
+        game.set_on_open(Box::new(move |position, count| {
+            sink.lock().expect("Lock taken").push((position, count));
+        }));
+        game.start().expect("Game started");
+
+        let outcome = game.open(Position(3, 2)).expect("Position opened");
+        let mut seen = seen.lock().expect("Lock taken").clone();
+
+        seen.sort();
+
+        assert_eq!(seen.len(), 11);
+        assert_eq!(seen, outcome.opened);
+    }
+
+    #[test]
+    fn not_kept_by_clone() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+        let calls = Arc::new(Mutex::new(0));
+        let counter = Arc::clone(&calls);
+
+        game.set_on_open(Box::new(move |_, _| {
+            *counter.lock().expect("Lock taken") += 1
+        }));
+        game.start().expect("Game started");
+
+        let mut copy = game.clone();
+
+        assert_eq!(copy, game);
+
+        copy.open(Position(2, 1)).expect("Position opened");
+
+        assert_eq!(*calls.lock().expect("Lock taken"), 0);
+    }
+
+    #[test]
+    fn game_keeps_auto_traits() {
+        fn assert_auto_traits<T: Send + Sync + UnwindSafe + RefUnwindSafe>() {}
+
+        assert_auto_traits::<Game>();
+    }
+}
+
+#[cfg(test)]
+mod game_set_topology {
+    use super::*;
+
+    #[test]
+    fn hex_interior_neighbors() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.set_topology(Topology::Hex).expect("Topology set");
+
+        assert_eq!(
+            game.neighbors(Position(2, 2)),
+            vec![
+                Position(1, 1),
+                Position(2, 1),
+                Position(1, 2),
+                Position(3, 2),
+                Position(1, 3),
+                Position(2, 3)
+            ]
+        );
+        assert_eq!(
+            game.neighbors(Position(2, 1)),
+            vec![
+                Position(2, 0),
+                Position(3, 0),
+                Position(1, 1),
+                Position(3, 1),
+                Position(2, 2),
+                Position(3, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_adjacent_mines() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(2, 3)).expect("Set mine");
+        // diagonal on square field, not a neighbour on hex one
+        game.mine(Position(3, 1)).expect("Set mine");
+        game.set_topology(Topology::Hex).expect("Topology set");
+
+        assert_eq!(game.adjacent_mines(Position(2, 2)), Ok(2));
+    }
+
+    #[test]
+    fn square_by_default() {
+        let game = Game::new(5, 5).expect("game created");
+
+        assert_eq!(game.topology, Topology::Square);
+        assert_eq!(game.neighbors(Position(2, 2)).len(), 8);
+    }
+
+    #[test]
+    fn wrapped_odd_height() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.set_wrap(true).expect("Wrap set");
+
+        assert_eq!(
+            game.set_topology(Topology::Hex),
+            Err(GameError::HexWrapOddHeight)
+        );
+        assert_eq!(game.topology, Topology::Square);
+    }
+}
+
+#[cfg(test)]
+mod game_set_connectivity {
+    use super::*;
+
+    #[test]
+    fn interior_neighbors() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(game.neighbors(Position(1, 1)).len(), 8);
+
+        game.set_connectivity(Connectivity::Orthogonal)
+            .expect("Connectivity set");
+
+        assert_eq!(
+            game.neighbors(Position(1, 1)),
+            vec![
+                Position(1, 0),
+                Position(0, 1),
+                Position(2, 1),
+                Position(1, 2)
+            ]
+        );
+        assert_eq!(game.neighbors(Position(0, 0)).len(), 2);
+    }
+
+    #[test]
+    fn diagonal_mine_not_counted() {
+        let mut game = Game::from_layout("*..\n...\n...").expect("game created");
+
+        game.set_connectivity(Connectivity::Orthogonal)
+            .expect("Connectivity set");
+        game.start().expect("Game started");
+
+        assert_eq!(game.adjacent_mines(Position(1, 1)), Ok(0));
+
+        game.open(Position(2, 2)).expect("Position opened");
+
+        // diagonal neighbour of the mine is zero, so cascade reaches everything
+        assert_eq!(game.open_positions.len(), 8);
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn hex_unaffected() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.set_topology(Topology::Hex).expect("Topology set");
+        game.set_connectivity(Connectivity::Orthogonal)
+            .expect("Connectivity set");
+
+        assert_eq!(game.neighbors(Position(2, 2)).len(), 6);
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_connectivity(Connectivity::Orthogonal),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_limit_flags {
+    use super::*;
+
+    #[test]
+    fn limit_reached() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.set_limit_flags(true).expect("Flag limit set");
+        game.start().expect("Game started");
+
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(0, 1)).expect("Position flagged");
+
+        assert_eq!(game.flag(Position(0, 2)), Err(GameError::FlagLimitReached));
+        assert_eq!(game.flag_positions.len(), 2);
+    }
+
+    #[test]
+    fn unlimited_by_default() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(0, 1)).expect("Position flagged");
+        game.flag(Position(0, 2)).expect("Position flagged");
+
+        assert_eq!(game.flag_positions.len(), 3);
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_limit_flags(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_clamp_remaining {
+    use super::*;
+
+    #[test]
+    fn clamped() {
+        let mut game = Game::from_layout("*.*\n...").expect("game created");
+
+        game.set_clamp_remaining(true).expect("Clamp set");
+        game.set_training_mode(true).expect("Training mode set");
+        game.start().expect("Game started");
+
+        game.flag(Position(1, 0)).expect("Position flagged");
+        game.flag(Position(1, 1)).expect("Position flagged");
+
+        assert_eq!(game.flag(Position(0, 1)), Err(GameError::FlagLimitReached));
+
+        // training mode flags opened mine regardless of the limit
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.flag_positions.len(), 3);
+        assert_eq!(game.remaining_mines(), 0);
+    }
+
+    #[test]
+    fn unclamped_by_default() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.start().expect("Game started");
+
+        game.flag(Position(1, 0)).expect("Position flagged");
+        game.flag(Position(2, 0)).expect("Position flagged");
+
+        assert_eq!(game.remaining_mines(), -1);
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_clamp_remaining(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_protect_flags {
+    use super::*;
+
+    #[test]
+    fn protected_flag() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.set_protect_flags(true).expect("Flag protection set");
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+
+        assert_eq!(game.open(Position(0, 0)), Err(GameError::CellFlagged));
+        assert_eq!(game.status, Status::InProgress);
+        assert!(game.flag_positions.contains(&Position(0, 0)));
+        assert_eq!(game.history.len(), 1);
+    }
+
+    #[test]
+    fn unprotected_by_default() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(2, 2)).expect("Position flagged");
+
+        game.open(Position(2, 2)).expect("Position opened");
+
+        assert!(game.flag_positions.is_empty());
+        assert!(game.open_positions.contains(&Position(2, 2)));
+    }
+
+    #[test]
+    fn open_after_unflag() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.set_protect_flags(true).expect("Flag protection set");
+        game.start().expect("Game started");
+        game.flag(Position(2, 2)).expect("Position flagged");
+        game.unflag(Position(2, 2)).expect("Position unflagged");
+
+        game.open(Position(2, 2)).expect("Position opened");
+
+        assert!(game.open_positions.contains(&Position(2, 2)));
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_protect_flags(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_allow_preflag {
+    use super::*;
+
+    #[test]
+    fn preflag_kept_after_start() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_allow_preflag(true).expect("Preflag allowed");
+        game.flag(Position(0, 0)).expect("Position flagged");
+
+        assert_eq!(
+            game.flag(Position(0, 0)),
+            Err(GameError::AlreadyFlagged(Position(0, 0)))
+        );
+        assert_eq!(
+            game.flag(Position(3, 0)),
+            Err(GameError::OutOfBounds(Position(3, 0)))
+        );
+
+        game.start().expect("Game started");
+
+        assert!(game.flag_positions.contains(&Position(0, 0)));
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+    }
+
+    #[test]
+    fn remove_preflag() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_allow_preflag(true).expect("Preflag allowed");
+        game.flag(Position(1, 0)).expect("Position flagged");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.unflag(Position(1, 0)).expect("Position unflagged");
+
+        assert_eq!(game.toggle_flag(Position(0, 0)), Ok(false));
+        assert_eq!(game.unflag(Position(2, 0)), Err(GameError::NotFlagged));
+
+        game.start().expect("Game started");
+
+        assert!(game.flag_positions.is_empty());
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        assert_eq!(game.flag(Position(0, 0)), Err(GameError::NotStarted));
+        assert!(game.flag_positions.is_empty());
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_allow_preflag(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_win_by_flags {
+    use super::*;
+
+    fn field() -> Game {
+        let mut game = Game::from_layout("*..\n..*\n...").expect("game created");
+
+        game.set_win_by_flags(true).expect("Win by flags set");
+        game.start().expect("Game started");
+        game
+    }
+
+    #[test]
+    fn flag_exactly_mines() {
+        let mut game = field();
+
+        game.flag(Position(0, 0)).expect("Position flagged");
+        assert_eq!(game.status, Status::InProgress);
+        game.flag(Position(2, 1)).expect("Position flagged");
+
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn flag_wrong_positions() {
+        let mut game = field();
+
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.flag(Position(2, 1)).expect("Position flagged");
+
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn undo_winning_flag() {
+        let mut game = field();
+
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(2, 1)).expect("Position flagged");
+        game.undo().expect("Move undone");
+
+        assert_eq!(game.status, Status::InProgress);
+        assert!(!game.flag_positions.contains(&Position(2, 1)));
+    }
+
+    #[test]
+    fn unflag_wrong_position() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_win_by_flags(true).expect("Win by flags set");
+        game.start().expect("Game started");
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.flag(Position(0, 0)).expect("Position flagged");
+
+        assert_eq!(game.status, Status::InProgress);
+
+        game.unflag(Position(1, 1)).expect("Position unflagged");
+
+        assert_eq!(game.status, Status::Won);
+
+        game.undo().expect("Move undone");
+
+        assert_eq!(game.status, Status::InProgress);
+        assert!(game.flag_positions.contains(&Position(1, 1)));
+    }
+
+    #[test]
+    fn preflagged_mines() {
+        let mut game = Game::from_layout("*..\n..*\n...").expect("game created");
+
+        game.set_win_by_flags(true).expect("Win by flags set");
+        game.set_allow_preflag(true).expect("Preflag allowed");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(2, 1)).expect("Position flagged");
+        game.start().expect("Game started");
+
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn no_mines_at_start() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.set_win_by_flags(true).expect("Win by flags set");
+        game.start().expect("Game started");
+
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let mut game = Game::from_layout("*..\n..*\n...").expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(2, 1)).expect("Position flagged");
+
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_win_by_flags(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_training_mode {
+    use super::*;
+
+    #[test]
+    fn mine_flagged() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_training_mode(true).expect("Training mode set");
+        game.start().expect("Game started");
+
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.status, Status::InProgress);
+        assert!(game.flag_positions.contains(&Position(0, 0)));
+        assert!(!game.open_positions.contains(&Position(0, 0)));
+        assert_eq!(game.lost_position(), None);
+    }
+
+    #[test]
+    fn win_needs_safe_positions() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_training_mode(true).expect("Training mode set");
+        game.start().expect("Game started");
+
+        game.open(Position(0, 0)).expect("Position opened");
+        assert_eq!(game.status, Status::InProgress);
+        game.open(Position(2, 1)).expect("Position opened");
+        assert_eq!(game.status, Status::InProgress);
+        game.open(Position(0, 1)).expect("Position opened");
+
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn undo_mine_flag() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_training_mode(true).expect("Training mode set");
+        game.start().expect("Game started");
+
+        game.open(Position(0, 0)).expect("Position opened");
+        game.undo().expect("Move undone");
+
+        assert!(game.flag_positions.is_empty());
+    }
+
+    #[test]
+    fn normal_mode_loses() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.start().expect("Game started");
+
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.status, Status::Lost);
+        assert!(game.flag_positions.is_empty());
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_training_mode(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_strict_chord {
+    use super::*;
+
+    fn wrong_flag(strict_chord: bool) -> Game {
+        let mut game = Game::from_layout("*..\n...\n...").expect("game created");
+
+        game.set_strict_chord(strict_chord)
+            .expect("Strict chord set");
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+        game.flag(Position(2, 2)).expect("Position flagged");
+        game.chord(Position(1, 1)).expect("Position chorded");
+
+        game
+    }
+
+    #[test]
+    fn strict_loses() {
+        let game = wrong_flag(true);
+
+        assert_eq!(game.status, Status::Lost);
+        assert_eq!(game.lost_position(), Some(Position(0, 0)));
+    }
+
+    #[test]
+    fn forgiving_opens_safe_neighbours() {
+        let game = wrong_flag(false);
+
+        assert_eq!(game.status, Status::InProgress);
+        assert_eq!(game.open_positions.len(), 7);
+        assert!(!game.open_positions.contains(&Position(0, 0)));
+        assert!(game.flag_positions.contains(&Position(2, 2)));
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_strict_chord(false),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_mercies {
+    use super::*;
+
+    #[test]
+    fn mercy_then_loss() {
+        let mut game = Game::from_layout("*.*\n...").expect("game created");
+
+        game.set_mercies(1).expect("Mercies set");
+        game.start().expect("Game started");
+
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.status, Status::InProgress);
+        assert!(game.flag_positions.contains(&Position(0, 0)));
+        assert_eq!(game.mercies_left(), 0);
+
+        game.open(Position(2, 0)).expect("Position opened");
+
+        assert_eq!(game.status, Status::Lost);
+        assert_eq!(game.lost_position(), Some(Position(2, 0)));
+    }
+
+    #[test]
+    fn reset_restores_mercies() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_mercies(2).expect("Mercies set");
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.mercies_left(), 1);
+
+        game.reset();
+
+        assert_eq!(game.mercies_left(), 2);
+    }
+
+    #[test]
+    fn undo_restores_mercy() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_mercies(1).expect("Mercies set");
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.unflag(Position(0, 0)).expect("Position unflagged");
+        game.open(Position(0, 0)).expect("Position opened");
+        game.undo().expect("Move undone");
+
+        assert_eq!(game.mercies_left(), 1);
+        assert!(game.flag_positions.is_empty());
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn none_by_default() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.status, Status::Lost);
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_mercies(1),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_set_cache_neighbors {
+    use super::*;
+
+    #[test]
+    fn cached_match_computed() {
+        for (topology, wrap) in [
+            (Topology::Square, false),
+            (Topology::Square, true),
+            (Topology::Hex, false),
+            (Topology::Hex, true),
+        ] {
+            let mut game = Game::new(30, 16).expect("game created");
+
+            game.set_topology(topology).expect("Topology set");
+            game.set_wrap(wrap).expect("Wrap set");
+            game.set_cache_neighbors(true).expect("Neighbor cache set");
+
+            for position in game.positions() {
+                assert_eq!(game.neighbors(position), game.compute_neighbors(position));
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn built_on_first_lookup() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.set_cache_neighbors(true).expect("Neighbor cache set");
+
+        assert!(!game.neighbor_cache.is_built());
+
+        game.adjacent_mines(Position(2, 2)).expect("Mines counted");
+
+        assert!(game.neighbor_cache.is_built());
+        assert!(matches!(
+            game.neighbor_slice(Position(2, 2)),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn not_cached_by_default() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+        game.neighbors(Position(2, 2));
+
+        assert!(!game.neighbor_cache.is_built());
+    }
+
+    #[test]
+    fn rebuilt_after_wrap_change() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.set_cache_neighbors(true).expect("Neighbor cache set");
+        assert_eq!(game.neighbors(Position(0, 0)).len(), 3);
+        game.set_wrap(true).expect("Wrap set");
+
+        assert!(!game.neighbor_cache.is_built());
+        assert_eq!(game.neighbors(Position(0, 0)).len(), 8);
+    }
+
+    #[test]
+    fn cache_ignored_in_comparison() {
+        let mut game = Game::new(5, 5).expect("game created");
+        let cached = game.clone();
+
+        game.set_cache_neighbors(true).expect("Neighbor cache set");
+        game.neighbors(Position(0, 0));
+        game.cache_neighbors = false;
+
+        assert_eq!(game, cached);
+    }
+
+    #[test]
+    fn set_in_progress_game() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.set_cache_neighbors(true),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_start {
+    use super::*;
+
+    #[test]
+    fn start_fresh_game() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn start_already_started_game() {
+        let mut game = Game::new(1, 1).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.start(),
+            Err(GameError::IncorrectStatus(
+                Status::InProgress,
+                Status::Configuration
+            ))
+        );
+    }
+
+    #[test]
+    fn all_mine_board() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.mine_positions.insert(Position(0, 0));
+        game.mine_positions.insert(Position(1, 0));
+
+        game.start().expect("Game started");
+
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn zero_mine_board() {
+        let mut game = Game::new(1, 1).expect("game created");
+
+        game.start().expect("Game started");
+        assert_eq!(game.status, Status::InProgress);
+
+        game.open(Position(0, 0)).expect("Position opened");
+        assert_eq!(game.status, Status::Won);
+    }
+}
+
+#[cfg(test)]
+mod game_open {
+    use super::*;
+
+    #[test]
+    fn open_in_config_game() {
+        let mut game = Game::new(1, 1).expect("game created");
+
+        assert_eq!(game.open(Position(1, 1)), Err(GameError::NotStarted));
+    }
+
+    #[test]
+    fn moves_after_win() {
+        let mut game = Game::from_layout("*.").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+
+        assert_eq!(game.open(Position(0, 0)), Err(GameError::GameOver));
+        assert_eq!(game.flag(Position(0, 0)), Err(GameError::GameOver));
+        assert_eq!(game.unflag(Position(0, 0)), Err(GameError::GameOver));
+        assert_eq!(game.chord(Position(1, 0)), Err(GameError::GameOver));
+    }
+
+    #[test]
+    fn open_safe_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let mine_position = Position(1, 1);
+        let safe_position = Position(1, 2);
+
+        game.mine(mine_position).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(safe_position).expect("Position opened");
+
+        assert_eq!(game.status, Status::InProgress);
+        assert!(game.open_positions.contains(&safe_position));
+    }
+
+    #[test]
+    fn open_mine_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let mine_position = Position(1, 1);
+
+        game.mine(mine_position).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(mine_position).expect("Position opened");
+
+        assert_eq!(game.status, Status::Lost);
+    }
+
+    #[test]
+    fn open_safe_position_twice() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let open = Position(1, 2);
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(open).expect("Position opened");
+
+        assert_eq!(game.open(open), Err(GameError::AlreadyOpened(open)));
+    }
+
+    #[test]
+    fn open_flagged_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let flag = Position(1, 2);
+
+        game.start().expect("Game started");
+
+        game.flag(flag).expect("Position flagged");
+        game.open(flag).expect("Position opened");
+
+        assert!(!game.flag_positions.contains(&flag));
+        assert!(game.open_positions.contains(&flag));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = Game::new(10, 10).expect("game created");
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.open(Position(11, 10)),
+            Err(GameError::OutOfBounds(Position(11, 10)))
+        );
+    }
+
+    #[test]
+    fn win_game() {
+        let mut game = Game::new(1, 2).expect("game created");
+
+        let mine = Position(0, 0);
+
+        game.mine(mine).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.flag(mine).expect("Position flagged");
+        game.open(Position(0, 1)).expect("Position opened");
+
+        assert!(matches!(game.status, Status::Won));
+    }
+
+    #[test]
+    fn win_without_flags() {
+        let mut game = Game::new(3, 1).expect("game created");
+
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(Position(0, 0)).expect("Position opened");
+        assert_eq!(game.status, Status::InProgress);
+
+        game.open(Position(2, 0)).expect("Position opened");
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn cascade_zero_cells() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(Position(2, 2)).expect("Position opened");
+
+        assert_eq!(game.open_positions.len(), 8);
+        assert!(!game.open_positions.contains(&Position(0, 0)));
+    }
+
+    #[test]
+    fn cascade_stops_at_numbers() {
+        let mut game = Game::new(5, 1).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine(Position(4, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(Position(2, 0)).expect("Position opened");
+
+        assert_eq!(game.open_positions.len(), 3);
+        assert!(game.open_positions.contains(&Position(1, 0)));
+        assert!(game.open_positions.contains(&Position(3, 0)));
+    }
+
+    #[test]
+    fn cascade_large_field() {
+        let mut game = Game::new(500, 500).expect("game created");
+
+        game.mine(Position(499, 499)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.open_positions.len(), 500 * 500 - 1);
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn cascade_skips_flagged() {
+        let mut game = Game::new(3, 1).expect("game created");
+
+        game.mine(Position(2, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.flag(Position(1, 0)).expect("Position flagged");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.open_positions.len(), 1);
+        assert!(game.flag_positions.contains(&Position(1, 0)));
+    }
+
+    #[test]
+    fn outcome_safe_open() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.open(Position(1, 0)),
+            Ok(OpenOutcome {
+                opened: vec![(Position(1, 0), 1)],
+                hit_mine: false,
+                status: Status::InProgress,
+            })
+        );
+    }
+
+    #[test]
+    fn outcome_cascade() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.open(Position(2, 1)),
+            Ok(OpenOutcome {
+                opened: vec![
+                    (Position(1, 0), 1),
+                    (Position(2, 0), 0),
+                    (Position(1, 1), 1),
+                    (Position(2, 1), 0)
+                ],
+                hit_mine: false,
+                status: Status::InProgress,
+            })
+        );
+    }
+
+    #[test]
+    fn outcome_counts_match_adjacent_mines() {
+        let mut game = Game::new_random(12, 8, 10, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        let safe = game
+            .positions()
+            .find(|position| {
+                game.adjacent_mines(*position) == Ok(0) && !game.mine_positions.contains(position)
+            })
+            .expect("Zero position found");
+        let outcome = game.open(safe).expect("Position opened");
+
+        assert!(outcome.opened.len() > 1);
+
+        for (position, count) in outcome.opened {
+            assert_eq!(game.adjacent_mines(position), Ok(count));
+        }
+    }
+
+    #[test]
+    fn outcome_mine_hit() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.open(Position(0, 0)),
+            Ok(OpenOutcome {
+                opened: Vec::new(),
+                hit_mine: true,
+                status: Status::Lost,
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_peek_open {
+    use super::*;
+
+    #[test]
+    fn cascade_not_applied() {
+        let mut game = Game::from_layout("*...\n....\n....").expect("game created");
+
+        game.start().expect("Game started");
+
+        let snapshot = game.clone();
+        let outcome = game.peek_open(Position(3, 2)).expect("Position peeked");
+
+        assert_eq!(outcome.opened.len(), 11);
+        assert_eq!(outcome.status, Status::Won);
+        assert_eq!(game, snapshot);
+        assert!(game.open_positions.is_empty());
+        assert_eq!(game.open(Position(3, 2)), Ok(outcome));
+    }
+
+    #[test]
+    fn mine_not_lost() {
+        let mut game = Game::from_layout("*..").expect("game created");
+
+        game.start().expect("Game started");
+
+        let outcome = game.peek_open(Position(0, 0)).expect("Position peeked");
+
+        assert!(outcome.hit_mine);
+        assert_eq!(outcome.status, Status::Lost);
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn error_passed_through() {
+        let game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(game.peek_open(Position(0, 0)), Err(GameError::NotStarted));
+    }
+}
+
+#[cfg(test)]
+mod game_reset {
+    use super::*;
+
+    #[test]
+    fn reset_lost_game() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(2, 2)).expect("Position flagged");
+        game.question(Position(1, 2)).expect("Position questioned");
+        game.open(Position(1, 1)).expect("Position opened");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        game.reset();
+
+        assert_eq!(game.status, Status::Configuration);
+        assert_eq!(game.mine_positions.len(), 2);
+        assert!(game.open_positions.is_empty());
+        assert!(game.flag_positions.is_empty());
+        assert!(game.question_positions.is_empty());
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+        #[cfg(feature = "std")]
+        assert_eq!(game.elapsed(), None);
+    }
+
+    #[test]
+    fn reset_mines() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.reset_mines();
+
+        assert_eq!(game.status, Status::Configuration);
+        assert!(game.mine_positions.is_empty());
+    }
+
+    #[test]
+    fn restart_lost_game() {
+        let mut game = Game::new_random(8, 8, 10, 11).expect("game created");
+        let mines = game.mine_positions.clone();
+        let mine = *mines.first().expect("Mine placed");
+
+        game.start().expect("Game started");
+        game.flag(Position(7, 7)).expect("Position flagged");
+        game.open(mine).expect("Position opened");
+
+        assert_eq!(game.status, Status::Lost);
+
+        game.restart().expect("Game restarted");
+
+        assert_eq!(game.status, Status::InProgress);
+        assert_eq!(game.mine_positions, mines);
+        assert!(game.open_positions.is_empty());
+        assert!(game.flag_positions.is_empty());
+        assert_eq!(game.lost_position(), None);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod game_elapsed {
+    use super::*;
+
+    #[test]
+    fn not_started() {
+        let game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(game.elapsed(), None);
+    }
+
+    #[test]
+    fn counting_in_progress() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.start().expect("Game started");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        assert!(game.elapsed().expect("game started") > Duration::ZERO);
+    }
+
+    #[test]
+    fn frozen_after_loss() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        let elapsed = game.elapsed();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        assert_eq!(game.elapsed(), elapsed);
+    }
+}
+
+#[cfg(test)]
+mod game_xy {
+    use super::*;
+
+    #[test]
+    fn open_xy_same_as_open() {
+        let mut by_position = Game::from_layout("*...\n....").expect("game created");
+        by_position.start().expect("Game started");
+        let mut by_coordinates = by_position.clone();
+
+        by_position
+            .open(Position::new(3, 1))
+            .expect("Position opened");
+        by_coordinates.open_xy(3, 1).expect("Position opened");
+
+        assert_eq!(by_coordinates, by_position);
+        assert_eq!(
+            by_coordinates.open_xy(3, 1),
+            by_position.open(Position::new(3, 1))
+        );
+        assert_eq!(
+            by_coordinates.open_xy(9, 1),
+            Err(GameError::OutOfBounds(Position(9, 1)))
+        );
+    }
+
+    #[test]
+    fn flag_xy_same_as_flag() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.start().expect("Game started");
+        game.flag_xy(2, 1).expect("Position flagged");
+
+        assert!(game.flag_positions.contains(&Position::new(2, 1)));
+        assert_eq!(
+            game.flag_xy(2, 1),
+            Err(GameError::AlreadyFlagged(Position(2, 1)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_open_returning {
+    use super::*;
+
+    #[test]
+    fn cascade_returns_all_opened() {
+        let mut game = Game::new(4, 1).expect("game created");
+
+        game.mine(Position(3, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        let opened = game
+            .open_returning(Position(0, 0))
+            .expect("Position opened");
+
+        assert_eq!(
+            opened,
+            PositionSet::from([Position(0, 0), Position(1, 0), Position(2, 0)])
+        );
+    }
+
+    #[test]
+    fn single_position() {
+        let mut game = Game::new(3, 1).expect("game created");
+
+        game.mine(Position(2, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        let opened = game
+            .open_returning(Position(1, 0))
+            .expect("Position opened");
+
+        assert_eq!(opened, PositionSet::from([Position(1, 0)]));
+    }
+
+    #[test]
+    fn mine_returns_empty() {
+        let mut game = Game::new(3, 1).expect("game created");
+
+        game.mine(Position(2, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        let opened = game
+            .open_returning(Position(2, 0))
+            .expect("Position opened");
+
+        assert!(opened.is_empty());
+        assert_eq!(game.status, Status::Lost);
+    }
+}
+
+#[cfg(test)]
+mod game_open_many {
+    use super::*;
+
+    #[test]
+    fn open_all() {
+        let mut game = Game::new(3, 1).expect("game created");
+
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.open_many(&[Position(0, 0), Position(2, 0)]),
+            Ok(Status::Won)
+        );
+    }
+
+    #[test]
+    fn stop_on_loss() {
+        let mut game = Game::new(4, 1).expect("game created");
+
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.open_many(&[Position(0, 0), Position(1, 0), Position(3, 0)]),
+            Ok(Status::Lost)
+        );
+        assert!(game.open_positions.contains(&Position(0, 0)));
+        assert!(!game.open_positions.contains(&Position(3, 0)));
+    }
+
+    #[test]
+    fn first_error() {
+        let mut game = Game::new(4, 1).expect("game created");
+
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.open_many(&[Position(0, 0), Position(7, 0), Position(3, 0)]),
+            Err(GameError::OutOfBounds(Position(7, 0)))
+        );
+        assert!(!game.open_positions.contains(&Position(3, 0)));
+    }
+}
+
+#[cfg(test)]
+mod game_flag {
+    use super::*;
+
+    #[test]
+    fn flag_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let flag_position = Position(1, 1);
+
+        game.start().expect("Game started");
+
+        game.flag(flag_position).expect("Position flagged");
+
+        assert!(game.flag_positions.contains(&flag_position));
+    }
+
+    #[test]
+    fn flag_before_start() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        assert_eq!(game.flag(Position(1, 1)), Err(GameError::NotStarted));
+    }
+
+    #[test]
+    fn flag_position_twice() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let flag_position = Position(1, 1);
+
+        game.start().expect("Game started");
+
+        game.flag(flag_position).expect("Position flagged");
+
+        assert_eq!(
+            game.flag(flag_position),
+            Err(GameError::AlreadyFlagged(flag_position))
+        );
+    }
+
+    #[test]
+    fn flag_open_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let open = Position(1, 1);
+
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(open).expect("Position opened");
+
+        assert_eq!(game.flag(open), Err(GameError::AlreadyOpened(open)));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.flag(Position(12, 8)),
+            Err(GameError::OutOfBounds(Position(12, 8)))
+        );
+    }
+
+    #[test]
+    fn flags_do_not_win() {
+        let mut game = Game::new(2, 2).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(1, 0)).expect("Position flagged");
+        game.flag(Position(0, 1)).expect("Position flagged");
+        game.flag(Position(1, 1)).expect("Position flagged");
+
+        assert_eq!(game.status, Status::InProgress);
+    }
+}
+
+#[cfg(test)]
+mod game_unflag {
+    use super::*;
+
+    #[test]
+    fn unflag_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let flag_position = Position(1, 1);
+
+        game.start().expect("Game started");
+
+        game.flag(flag_position).expect("Position flagged");
+        game.unflag(flag_position).expect("Position unflagged");
+
+        assert!(!game.flag_positions.contains(&flag_position));
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn unflag_before_start() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        assert_eq!(game.unflag(Position(1, 1)), Err(GameError::NotStarted));
+    }
+
+    #[test]
+    fn unflag_not_flagged() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(game.unflag(Position(1, 1)), Err(GameError::NotFlagged));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.unflag(Position(10, 3)),
+            Err(GameError::OutOfBounds(Position(10, 3)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_clear_flags {
+    use super::*;
+
+    #[test]
+    fn clear_several_flags() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.flag(Position(2, 2)).expect("Position flagged");
+
+        game.clear_flags().expect("Flags cleared");
+
+        assert!(game.flag_positions.is_empty());
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn undo_restores_flag() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.clear_flags().expect("Flags cleared");
+
+        game.undo().expect("Move undone");
+
+        assert!(game.flag_positions.contains(&Position(1, 1)));
+    }
+
+    #[test]
+    fn clear_in_config_game() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(game.clear_flags(), Err(GameError::NotStarted));
+    }
+}
+
+#[cfg(test)]
+mod game_toggle_flag {
+    use super::*;
+
+    #[test]
+    fn toggle_on() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(game.toggle_flag(Position(1, 1)), Ok(true));
+        assert!(game.flag_positions.contains(&Position(1, 1)));
+    }
+
+    #[test]
+    fn toggle_off() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(1, 1)).expect("Position flagged");
+
+        assert_eq!(game.toggle_flag(Position(1, 1)), Ok(false));
+        assert!(!game.flag_positions.contains(&Position(1, 1)));
+    }
+
+    #[test]
+    fn toggle_open_position() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+
+        assert_eq!(
+            game.toggle_flag(Position(1, 1)),
+            Err(GameError::AlreadyOpened(Position(1, 1)))
+        );
+    }
+
+    #[test]
+    fn toggle_before_start() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        assert_eq!(game.toggle_flag(Position(1, 1)), Err(GameError::NotStarted));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.toggle_flag(Position(5, 5)),
+            Err(GameError::OutOfBounds(Position(5, 5)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_question {
+    use super::*;
+
+    #[test]
+    fn question_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+        game.question(Position(1, 1)).expect("Position questioned");
+
+        assert!(game.question_positions.contains(&Position(1, 1)));
+    }
+
+    #[test]
+    fn flag_to_question() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let position = Position(1, 1);
+
+        game.start().expect("Game started");
+        game.flag(position).expect("Position flagged");
+        game.question(position).expect("Position questioned");
+
+        assert!(!game.flag_positions.contains(&position));
+        assert!(game.question_positions.contains(&position));
+    }
+
+    #[test]
+    fn question_to_flag() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let position = Position(1, 1);
+
+        game.start().expect("Game started");
+        game.question(position).expect("Position questioned");
+        game.flag(position).expect("Position flagged");
+
+        assert!(game.flag_positions.contains(&position));
+        assert!(!game.question_positions.contains(&position));
+    }
+
+    #[test]
+    fn open_questioned_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        let position = Position(1, 2);
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+        game.question(position).expect("Position questioned");
+        game.open(position).expect("Position opened");
+
+        assert!(!game.question_positions.contains(&position));
+        assert!(game.open_positions.contains(&position));
+    }
+
+    #[test]
+    fn question_twice() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+        game.question(Position(1, 1)).expect("Position questioned");
+
+        assert_eq!(
+            game.question(Position(1, 1)),
+            Err(GameError::AlreadyQuestioned)
+        );
+    }
+
+    #[test]
+    fn question_open_position() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+
+        assert_eq!(
+            game.question(Position(1, 1)),
+            Err(GameError::AlreadyOpened(Position(1, 1)))
+        );
+    }
+
+    #[test]
+    fn question_before_start() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        assert_eq!(game.question(Position(1, 1)), Err(GameError::NotStarted));
+    }
+
+    #[test]
+    fn question_after_win() {
+        let mut game = Game::from_layout("*.").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+
+        assert_eq!(game.question(Position(0, 0)), Err(GameError::GameOver));
+        assert_eq!(game.clear_flags(), Err(GameError::GameOver));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.question(Position(10, 0)),
+            Err(GameError::OutOfBounds(Position(10, 0)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_undo {
+    use super::*;
+
+    #[test]
+    fn undo_flag() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.undo().expect("Move undone");
+
+        assert!(game.flag_positions.is_empty());
+    }
+
+    #[test]
+    fn undo_unflag() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.unflag(Position(1, 1)).expect("Position unflagged");
+        game.undo().expect("Move undone");
+
+        assert!(game.flag_positions.contains(&Position(1, 1)));
+    }
+
+    #[test]
+    fn undo_question() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.question(Position(1, 1)).expect("Position questioned");
+        game.undo().expect("Move undone");
+
+        assert!(game.flag_positions.contains(&Position(1, 1)));
+        assert!(game.question_positions.is_empty());
+    }
+
+    #[test]
+    fn undo_safe_open() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(1, 2)).expect("Position flagged");
+        game.open(Position(1, 2)).expect("Position opened");
+        game.undo().expect("Move undone");
+
+        assert!(game.open_positions.is_empty());
+        assert!(game.flag_positions.contains(&Position(1, 2)));
+    }
+
+    #[test]
+    fn undo_cascade() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(4, 4)).expect("Position opened");
+        game.undo().expect("Move undone");
+
+        assert!(game.open_positions.is_empty());
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn undo_loss() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+        game.undo().expect("Move undone");
+
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn nothing_to_undo() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+    }
+}
+
+#[cfg(test)]
+mod game_move_log {
+    use super::*;
+
+    fn field() -> Game {
+        let mut game = Game::from_layout("*...\n..*.\n....").expect("game created");
+
+        game.start().expect("Game started");
+        game
+    }
+
+    #[test]
+    fn export() {
+        let mut game = field();
+
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.question(Position(3, 0)).expect("Position questioned");
+        game.open(Position(1, 0)).expect("Position opened");
+        game.unflag(Position(0, 0)).expect("Position unflagged");
+
+        assert_eq!(game.export_moves(), "F 0 0\nQ 3 0\nO 1 0\nU 0 0\n");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut game = field();
+
+        game.open(Position(0, 2)).expect("Position opened");
+        game.flag(Position(2, 1)).expect("Position flagged");
+        game.question(Position(0, 0)).expect("Position questioned");
+        game.open(Position(3, 0)).expect("Position opened");
+
+        let mut replayed = field();
+
+        replayed
+            .apply_move_log(&game.export_moves())
+            .expect("Moves applied");
+
+        assert_eq!(replayed, game);
+    }
+
+    #[test]
+    fn round_trip_mercy() {
+        let field = || {
+            let mut game = Game::from_layout("*.*\n...\n...").expect("game created");
+
+            game.set_mercies(1).expect("Mercies set");
+            game.start().expect("Game started");
+            game
+        };
+        let mut game = field();
+
+        game.open(Position(0, 0)).expect("Position opened");
+        game.open(Position(2, 0)).expect("Position opened");
+
+        assert_eq!(game.export_moves(), "O 0 0\nO 2 0\n");
+
+        let mut replayed = field();
+
+        replayed
+            .apply_move_log(&game.export_moves())
+            .expect("Moves applied");
+
+        assert_eq!(replayed.status, Status::Lost);
+        assert_eq!(replayed, game);
+    }
+
+    #[test]
+    fn malformed_log() {
+        let mut game = field();
+
+        for log in ["O 1 0\nX 1 1", "O 1", "O a 0", "O 1 0 2"] {
+            assert_eq!(game.apply_move_log(log), Err(GameError::InvalidMoveLog));
+        }
+        assert!(game.open_positions.is_empty());
+    }
+
+    #[test]
+    fn failed_move() {
+        let mut game = field();
+
+        assert_eq!(
+            game.apply_move_log("O 1 0\nO 1 0"),
+            Err(GameError::AlreadyOpened(Position(1, 0)))
+        );
+        assert!(game.open_positions.contains(&Position(1, 0)));
+    }
+}
+
+#[cfg(test)]
+mod game_chord {
+    use super::*;
+
+    #[test]
+    fn chord_opens_neighbours() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        let mine = Position(0, 0);
+
+        game.mine(mine).expect("Set mine");
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(Position(1, 1)).expect("Position opened");
+        game.flag(mine).expect("Position flagged");
+        game.flag(Position(2, 2)).expect("Position flagged");
+        game.chord(Position(1, 1)).expect("Position chorded");
+
+        assert_eq!(game.open_positions.len(), 7);
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn chord_wrong_flag() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(Position(1, 1)).expect("Position opened");
+        game.flag(Position(2, 2)).expect("Position flagged");
+        game.chord(Position(1, 1)).expect("Position chorded");
+
+        assert_eq!(game.status, Status::Lost);
+    }
+
+    #[test]
+    fn flag_count_mismatch() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(Position(1, 1)).expect("Position opened");
+
+        assert_eq!(game.chord(Position(1, 1)), Err(GameError::NotChordable));
+    }
+
+    #[test]
+    fn chord_hidden_position() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(game.chord(Position(1, 1)), Err(GameError::NotChordable));
+    }
+
+    #[test]
+    fn chord_before_start() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(game.chord(Position(1, 1)), Err(GameError::NotStarted));
+    }
+}
+
+#[cfg(test)]
+mod game_apply {
+    use super::*;
+
+    #[test]
+    fn start() {
+        let mut game = Game::new(2, 2).expect("game created");
+
+        assert_eq!(
+            game.apply(Action::Start),
+            Ok(OpenOutcome {
+                opened: Vec::new(),
+                hit_mine: false,
+                status: Status::InProgress,
+            })
+        );
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn open() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.apply(Action::Start).expect("Game started");
+
+        assert_eq!(
+            game.apply(Action::Open(Position(1, 0))),
+            Ok(OpenOutcome {
+                opened: vec![(Position(1, 0), 1)],
+                hit_mine: false,
+                status: Status::InProgress,
+            })
+        );
+    }
+
+    #[test]
+    fn flag_and_unflag() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.apply(Action::Start).expect("Game started");
+        game.apply(Action::Flag(Position(0, 0)))
+            .expect("Position flagged");
+
+        assert!(game.flag_positions.contains(&Position(0, 0)));
+
+        game.apply(Action::Unflag(Position(0, 0)))
+            .expect("Position unflagged");
+
+        assert!(game.flag_positions.is_empty());
+    }
+
+    #[test]
+    fn chord() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.apply(Action::Start).expect("Game started");
+        game.apply(Action::Open(Position(1, 1)))
+            .expect("Position opened");
+        game.apply(Action::Flag(Position(0, 0)))
+            .expect("Position flagged");
+
+        let outcome = game
+            .apply(Action::Chord(Position(1, 1)))
+            .expect("Position chorded");
+
+        assert_eq!(outcome.status, Status::Won);
+        assert!(!outcome.hit_mine);
+        assert_eq!(outcome.opened.len(), 4);
+    }
+
+    #[test]
+    fn chord_wrong_flag() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.apply(Action::Start).expect("Game started");
+        game.apply(Action::Open(Position(1, 1)))
+            .expect("Position opened");
+        game.apply(Action::Flag(Position(0, 1)))
+            .expect("Position flagged");
+
+        let outcome = game
+            .apply(Action::Chord(Position(1, 1)))
+            .expect("Position chorded");
+
+        assert_eq!(outcome.status, Status::Lost);
+        assert!(outcome.hit_mine);
+    }
+
+    #[test]
+    fn chord_wrong_flag_training() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.set_training_mode(true).expect("Training mode set");
+        game.apply(Action::Start).expect("Game started");
+        game.apply(Action::Open(Position(1, 1)))
+            .expect("Position opened");
+        game.apply(Action::Flag(Position(0, 1)))
+            .expect("Position flagged");
+
+        let outcome = game
+            .apply(Action::Chord(Position(1, 1)))
+            .expect("Position chorded");
+
+        assert_eq!(outcome.status, Status::InProgress);
+        assert!(outcome.hit_mine);
+        assert!(game.flag_positions.contains(&Position(0, 0)));
+    }
+
+    #[test]
+    fn error_passed_through() {
+        let mut game = Game::new(2, 2).expect("game created");
+
+        assert_eq!(
+            game.apply(Action::Open(Position(0, 0))),
+            Err(GameError::NotStarted)
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_auto_flag_obvious {
+    use super::*;
+
+    #[test]
+    fn flag_forced_mine() {
+        let mut game = Game::from_layout("..*..").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.auto_flag_obvious(), 1);
+        assert_eq!(game.flag_positions, PositionSet::from([Position(2, 0)]));
+        assert_eq!(game.auto_flag_obvious(), 0);
+    }
+
+    #[test]
+    fn flag_shared_mines_once() {
+        let mut game = Game::from_layout("*..\n...\n...").expect("game created");
+
+        game.start().expect("Game started");
+        // every safe position but the bottom right one, without cascade
+        for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1), (2, 1), (0, 2), (1, 2)] {
+            game.open_positions.insert(Position(x, y));
+        }
+
+        assert_eq!(game.auto_flag_obvious(), 1);
+        assert_eq!(game.flag_positions, PositionSet::from([Position(0, 0)]));
+    }
+
+    #[test]
+    fn nothing_forced() {
+        let mut game = Game::from_layout("*..\n...\n...").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+
+        assert_eq!(game.auto_flag_obvious(), 0);
+        assert!(game.flag_positions.is_empty());
+    }
+
+    #[test]
+    fn config_game() {
+        let mut game = Game::from_layout("*.").expect("game created");
+
+        game.open_positions.insert(Position(1, 0));
+
+        assert_eq!(game.auto_flag_obvious(), 0);
+        assert!(game.flag_positions.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod game_open_all_safe {
+    use super::*;
+
+    #[test]
+    fn clear_deducible_region() {
+        let mut game = Game::from_layout(".*.\n...\n...").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+        game.flag(Position(1, 0)).expect("Position flagged");
+
+        assert_eq!(game.open_all_safe(), 7);
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn nothing_deducible() {
+        let mut game = Game::from_layout("..*..").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.open_all_safe(), 0);
+        assert_eq!(game.open_positions.len(), 2);
+    }
+
+    #[test]
+    fn stop_after_wrong_flag() {
+        let mut game = Game::from_layout(".*.\n...").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+        game.flag(Position(0, 1)).expect("Position flagged");
+
+        game.open_all_safe();
+
+        assert_eq!(game.status, Status::Lost);
+    }
+
+    #[test]
+    fn config_game() {
+        let mut game = Game::from_layout("..*..").expect("game created");
+
+        assert_eq!(game.open_all_safe(), 0);
+    }
+}
+
+#[cfg(test)]
+mod game_auto_open_zeros {
+    use super::*;
+
+    #[test]
+    fn open_empty_regions() {
+        let mut game = Game::from_layout(
+            "......*.\n\
+             ......*.\n\
+             ******..\n\
+             ........\n\
+             ...*....",
+        )
+        .expect("game created");
+
+        game.start().expect("Game started");
+        game.auto_open_zeros();
+
+        // top left region with its border
+        for position in game
+            .positions()
+            .filter(|position| position.1 < 2 && position.0 < 6)
+        {
+            assert!(game.open_positions.contains(&position));
+        }
+        // region below the mine row
+        assert!(game.open_positions.contains(&Position(0, 4)));
+        assert!(game.open_positions.contains(&Position(7, 4)));
+        // right column has no zero position next to it
+        assert!(!game.open_positions.contains(&Position(7, 0)));
+        assert!(game
+            .mine_positions
+            .iter()
+            .all(|mine| !game.open_positions.contains(mine)));
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn win_when_all_opened() {
+        let mut game = Game::from_layout("*...\n....\n....").expect("game created");
+
+        game.start().expect("Game started");
+        game.auto_open_zeros();
+
+        assert_eq!(game.status, Status::Won);
+    }
+
+    #[test]
+    fn config_game() {
+        let mut game = Game::from_layout("*...").expect("game created");
+
+        game.auto_open_zeros();
+
+        assert!(game.open_positions.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod game_percent_complete {
+    use super::*;
+
+    #[test]
+    fn fresh_game() {
+        let mut game = Game::new(4, 1).expect("game created");
+
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(game.percent_complete(), 0.0);
+    }
+
+    #[test]
+    fn half_cleared() {
+        let mut game = Game::new(5, 1).expect("game created");
+
+        game.mine(Position(2, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.percent_complete(), 50.0);
+    }
+
+    #[test]
+    fn won_game() {
+        let mut game = Game::new(3, 1).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(2, 0)).expect("Position opened");
+
+        assert_eq!(game.status, Status::Won);
+        assert_eq!(game.percent_complete(), 100.0);
+    }
+
+    #[test]
+    fn no_safe_positions() {
+        let mut game = Game::new(1, 1).expect("game created");
+
+        game.mine_positions.insert(Position(0, 0));
+
+        assert_eq!(game.percent_complete(), 0.0);
+    }
+}
+
+#[cfg(test)]
+mod game_flag_density {
+    use super::*;
+
+    #[test]
+    fn no_flags() {
+        let mut game = Game::new(4, 4).expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(game.flag_density(), 0.0);
+    }
+
+    #[test]
+    fn some_flags() {
+        let mut game = Game::new(2, 2).expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+
+        assert_eq!(game.flag_density(), 0.25);
+    }
+
+    #[test]
+    fn all_flagged() {
+        let mut game = Game::new(2, 2).expect("game created");
+
+        game.start().expect("Game started");
+        for position in game.positions().collect::<Vec<Position>>() {
+            game.flag(position).expect("Position flagged");
+        }
+
+        assert_eq!(game.flag_density(), 1.0);
+    }
+}
+
+#[cfg(test)]
+mod game_remaining_mines {
+    use super::*;
+
+    #[test]
+    fn no_flags() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(2, 2)).expect("Set mine");
+
+        assert_eq!(game.remaining_mines(), 2);
+    }
+
+    #[test]
+    fn some_flags() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.flag(Position(1, 1)).expect("Position flagged");
+
+        assert_eq!(game.remaining_mines(), 1);
+    }
+
+    #[test]
+    fn over_flagged() {
+        let mut game = Game::new(10, 10).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.flag(Position(1, 1)).expect("Position flagged");
+        game.flag(Position(5, 5)).expect("Position flagged");
+        game.flag(Position(6, 6)).expect("Position flagged");
+
+        assert_eq!(game.remaining_mines(), -2);
+    }
+}
+
+#[cfg(test)]
+mod game_hidden_count {
+    use super::*;
+
+    #[test]
+    fn fresh_game() {
+        let mut game = Game::new(4, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(game.hidden_count(), 12);
+    }
+
+    #[test]
+    fn after_opens_and_flags() {
+        let mut game = Game::from_layout("*...\n*...\n***.").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+        game.open(Position(1, 1)).expect("Position opened");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.question(Position(0, 1)).expect("Position questioned");
+
+        assert_eq!(game.hidden_count(), 9);
+    }
+}
+
+#[cfg(test)]
+mod game_cell_at {
+    use super::*;
+
+    #[test]
+    fn hidden() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+
+        assert_eq!(game.cell_at(Position(1, 1)), Ok(CellState::Hidden));
+        assert_eq!(game.cell_at(Position(0, 0)), Ok(CellState::Hidden));
+    }
+
+    #[test]
+    fn mine_hidden_in_progress() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(game.cell_at(Position(0, 0)), Ok(CellState::Hidden));
+    }
+
+    #[test]
+    fn flagged() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(2, 2)).expect("Position flagged");
+
+        assert_eq!(game.cell_at(Position(2, 2)), Ok(CellState::Flagged));
+    }
+
+    #[test]
+    fn questioned() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.start().expect("Game started");
+        game.question(Position(2, 2)).expect("Position questioned");
+
+        assert_eq!(game.cell_at(Position(2, 2)), Ok(CellState::Questioned));
+    }
+
+    #[test]
+    fn open() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine(Position(2, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+
+        assert_eq!(game.cell_at(Position(1, 1)), Ok(CellState::Open(2)));
+    }
+
+    #[test]
+    fn mine_after_loss() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.cell_at(Position(0, 0)), Ok(CellState::Mine));
+        assert_eq!(game.cell_at(Position(2, 2)), Ok(CellState::Mine));
+    }
+
+    #[test]
+    fn mine_after_win() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+
+        assert_eq!(game.cell_at(Position(0, 0)), Ok(CellState::Mine));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(
+            game.cell_at(Position(3, 0)),
+            Err(GameError::OutOfBounds(Position(3, 0)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_to_grid {
+    use super::*;
+
+    #[test]
+    fn indexed_by_row() {
+        let mut game = Game::from_layout("*..\n..*").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(2, 0)).expect("Position opened");
+        game.flag(Position(0, 1)).expect("Position flagged");
+
+        let grid = game.to_grid();
+
+        assert_eq!(grid.len(), 2);
+        assert!(grid.iter().all(|row| row.len() == 3));
+        assert_eq!(grid[0][2], CellState::Open(1));
+        assert_eq!(grid[1][0], CellState::Flagged);
+        assert_eq!(grid[0][0], CellState::Hidden);
+    }
+
+    #[test]
+    fn mines_after_win() {
+        let mut game = Game::from_layout("*.").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+
+        assert_eq!(
+            game.to_grid(),
+            vec![vec![CellState::Mine, CellState::Open(1)]]
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_visible_state {
+    use super::*;
+
+    #[test]
+    fn no_mines_in_progress() {
+        let mut game = Game::from_layout("*..\n..*").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(2, 0)).expect("Position opened");
+
+        let state = game.visible_state();
+
+        assert_eq!(state.len(), 2);
+        assert_eq!(state[0][2], CellState::Open(1));
+        assert!(state.iter().flatten().all(|cell| *cell != CellState::Mine));
+    }
+
+    #[test]
+    fn no_mines_after_win() {
+        let mut game = Game::from_layout("*.").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+
+        assert_eq!(
+            game.visible_state(),
+            vec![vec![CellState::Hidden, CellState::Open(1)]]
+        );
+    }
+
+    #[test]
+    fn mines_after_loss() {
+        let mut game = Game::from_layout("*..\n..*").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        let state = game.visible_state();
+
+        assert_eq!(state[0][0], CellState::Mine);
+        assert_eq!(state[1][2], CellState::Mine);
+    }
+}
+
+#[cfg(test)]
+mod game_reveal_region {
+    use super::*;
+
+    #[test]
+    fn valid_region() {
+        let game = Game::from_layout("*..\n...\n..*").expect("game created");
+
+        assert_eq!(
+            game.reveal_region(Position(0, 0), Position(1, 1)),
+            Ok(vec![
+                (Position(0, 0), CellState::Mine),
+                (Position(1, 0), CellState::Open(1)),
+                (Position(0, 1), CellState::Open(1)),
+                (Position(1, 1), CellState::Open(2))
+            ])
+        );
+        assert!(game.open_positions.is_empty());
+    }
+
+    #[test]
+    fn single_position() {
+        let game = Game::from_layout("*..\n...\n..*").expect("game created");
+
+        assert_eq!(
+            game.reveal_region(Position(2, 0), Position(2, 0)),
+            Ok(vec![(Position(2, 0), CellState::Open(0))])
+        );
+    }
+
+    #[test]
+    fn inverted_corners() {
+        let game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(
+            game.reveal_region(Position(2, 0), Position(0, 2)),
+            Err(GameError::InvalidRegion)
+        );
+    }
+
+    #[test]
+    fn corner_out_of_bounds() {
+        let game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(
+            game.reveal_region(Position(0, 0), Position(3, 2)),
+            Err(GameError::OutOfBounds(Position(3, 2)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_is_mine {
+    use super::*;
+
+    #[test]
+    fn mined_position() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+
+        assert_eq!(game.is_mine(Position(1, 1)), Ok(true));
+        game.start().expect("Game started");
+        assert_eq!(game.is_mine(Position(1, 1)), Ok(true));
+        assert_eq!(game.status, Status::InProgress);
+    }
+
+    #[test]
+    fn safe_position() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+
+        assert_eq!(game.is_mine(Position(0, 1)), Ok(false));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let game = Game::new(3, 3).expect("game created");
+
+        assert_eq!(
+            game.is_mine(Position(3, 1)),
+            Err(GameError::OutOfBounds(Position(3, 1)))
+        );
+    }
+}
+
+#[cfg(all(test, debug_assertions))]
+mod game_is_flag_correct {
+    use super::*;
+
+    #[test]
+    fn correct_and_wrong_flags() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(2, 1)).expect("Position flagged");
+
+        assert_eq!(game.is_flag_correct(Position(0, 0)), Some(true));
+        assert_eq!(game.is_flag_correct(Position(2, 1)), Some(false));
+        assert_eq!(game.is_flag_correct(Position(1, 0)), None);
+    }
+}
+
+#[cfg(test)]
+mod game_lost_position {
+    use super::*;
+
+    #[test]
+    fn not_lost() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(1, 2)).expect("Position opened");
+
+        assert_eq!(game.lost_position(), None);
+    }
+
+    #[test]
+    fn opened_mine() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(3, 3)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(3, 3)).expect("Position opened");
+
+        assert_eq!(game.lost_position(), Some(Position(3, 3)));
+    }
+
+    #[test]
+    fn undone_loss() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+        game.undo().expect("Move undone");
+
+        assert_eq!(game.lost_position(), None);
+    }
+}
+
+#[cfg(test)]
+mod game_revealed_mines {
+    use super::*;
+
+    #[test]
+    fn hidden_in_progress() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert!(game.revealed_mines().is_empty());
+    }
+
+    #[test]
+    fn revealed_after_loss() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(3, 4)).expect("Set mine");
+        game.start().expect("Game started");
+
+        game.open(Position(1, 1)).expect("Position opened");
+
+        assert_eq!(game.revealed_mines(), game.mine_positions);
+    }
+}
+
+#[cfg(test)]
+mod game_solution {
+    use super::*;
+
+    #[test]
+    fn hidden_mid_game() {
+        let mut game = Game::from_layout("..*\n*..").expect("game created");
+
+        assert_eq!(game.solution(), None);
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+
+        assert_eq!(game.solution(), None);
+    }
+
+    #[test]
+    fn shown_after_loss() {
+        let mut game = Game::from_layout("..*\n*..").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(2, 0)).expect("Position opened");
+
+        assert_eq!(game.solution(), Some(vec![Position(2, 0), Position(0, 1)]));
+    }
+}
+
+#[cfg(test)]
+mod game_safest_cell {
+    use super::*;
+
+    #[test]
+    fn nothing_opened() {
+        let mut game = Game::new(3, 3).expect("game created");
+
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(game.safest_cell(), Some(Position(0, 0)));
+    }
+
+    #[test]
+    fn avoid_obvious_mine() {
+        let mut game = Game::new(4, 1).expect("game created");
+
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.safest_cell(), Some(Position(2, 0)));
+    }
+
+    #[test]
+    fn skip_flagged() {
+        let mut game = Game::new(3, 1).expect("game created");
+
+        game.mine(Position(2, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+
+        assert_eq!(game.safest_cell(), Some(Position(1, 0)));
+    }
+
+    #[test]
+    fn nothing_hidden() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(1, 0)).expect("Position flagged");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        assert_eq!(game.safest_cell(), None);
+    }
+}
+
+#[cfg(test)]
+mod game_is_solvable_without_guessing {
+    use super::*;
+
+    #[test]
+    fn solvable() {
+        let mut game = Game::from_layout("**.\n...\n...").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 2)).expect("Position opened");
+
+        let snapshot = game.clone();
+
+        assert_eq!(game.status, Status::InProgress);
+        assert!(game.is_solvable_without_guessing());
+        assert_eq!(game, snapshot);
+    }
+
+    #[test]
+    fn needs_guess() {
+        let mut game = Game::from_layout("*.\n..\n..").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(1, 2)).expect("Position opened");
+
+        assert!(!game.is_solvable_without_guessing());
+    }
+
+    #[test]
+    fn nothing_opened() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+
+        game.start().expect("Game started");
+
+        assert!(!game.is_solvable_without_guessing());
+    }
+}
+
+#[cfg(test)]
+mod game_three_bv {
+    use super::*;
+
+    #[test]
+    fn zero_regions_and_numbers() {
+        let game = Game::from_layout("*..\n...\n..*").expect("game created");
+
+        assert_eq!(game.three_bv(), 2);
+    }
+
+    #[test]
+    fn isolated_numbers() {
+        let game = Game::from_layout("*.*\n...\n*.*").expect("game created");
+
+        assert_eq!(game.three_bv(), 5);
+    }
+
+    #[test]
+    fn mixed_board() {
+        let game = Game::from_layout("*....\n.....\n.*...\n.....").expect("game created");
+
+        assert_eq!(game.three_bv(), 5);
+    }
+
+    #[test]
+    fn no_mines() {
+        let game = Game::new(4, 3).expect("game created");
+
+        assert_eq!(game.three_bv(), 1);
+    }
+}
+
+#[cfg(test)]
+mod game_open_region_count {
+    use super::*;
+
+    #[test]
+    fn separated_patches() {
+        let mut game = Game::from_layout(".....\n*****\n.....").expect("game created");
+
+        game.start().expect("Game started");
+
+        assert_eq!(game.open_region_count(), 0);
+
+        game.open(Position(0, 0)).expect("Position opened");
+        game.open(Position(1, 0)).expect("Position opened");
+        game.open(Position(4, 2)).expect("Position opened");
+
+        assert_eq!(game.open_region_count(), 2);
+    }
+
+    #[test]
+    fn diagonal_patches() {
+        let mut game = Game::from_layout("*.*\n.*.").expect("game created");
+
+        game.set_connectivity(Connectivity::Orthogonal)
+            .expect("Connectivity set");
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+        game.open(Position(0, 1)).expect("Position opened");
+
+        assert_eq!(game.open_region_count(), 2);
+
+        let mut king = Game::from_layout("*.*\n.*.").expect("game created");
+
+        king.start().expect("Game started");
+        king.open(Position(1, 0)).expect("Position opened");
+        king.open(Position(0, 1)).expect("Position opened");
+
+        assert_eq!(king.open_region_count(), 1);
+    }
+}
+
+#[cfg(test)]
+mod game_to_bytes {
+    use super::*;
+
+    fn mid_game() -> Game {
+        let mut game = Game::from_layout("*...\n....\n..*.").expect("game created");
+
+        game.mine_with_count(Position(3, 0), 2).expect("Set mine");
+        game.set_protect_flags(true).expect("Flags protected");
+        game.set_topology(Topology::Hex).expect("Topology set");
+        game.start().expect("Game started");
+        game.open(Position(1, 1)).expect("Position opened");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.question(Position(3, 2)).expect("Position questioned");
+
+        game
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut game = mid_game();
+
+        let restored = Game::from_bytes(&game.to_bytes()).expect("game restored");
+
+        assert!(restored.history.is_empty());
+
+        game.history.clear();
+
+        assert_eq!(restored, game);
+    }
+
+    #[test]
+    fn round_trip_lost() {
+        let mut game = mid_game();
+
+        game.open(Position(2, 2)).expect("Position opened");
+
+        let restored = Game::from_bytes(&game.to_bytes()).expect("game restored");
+
+        assert_eq!(restored.status, Status::Lost);
+        assert_eq!(restored.lost_position(), Some(Position(2, 2)));
+        assert_eq!(restored.to_grid(), game.to_grid());
+    }
+
+    #[test]
+    fn truncated() {
+        let data = mid_game().to_bytes();
+
+        for length in 0..data.len() {
+            assert_eq!(
+                Game::from_bytes(&data[..length]),
+                Err(GameError::InvalidSaveData)
+            );
+        }
+    }
+
+    // offsets in save of started, not lost game
+    const STATUS: usize = 21;
+    const SETTINGS: usize = 22;
+    const CELLS: usize = 29;
+
+    fn started(layout: &str) -> Vec<u8> {
+        let mut game = Game::from_layout(layout).expect("game created");
+
+        game.start().expect("Game started");
+        game.to_bytes()
+    }
+
+    #[test]
+    fn opened_and_flagged() {
+        let mut data = started("*.");
+
+        data[CELLS] = 0b0001 | 0b0110 << 4;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+
+        data[CELLS] = 0b0001 | 0b1010 << 4;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
+
+    #[test]
+    fn flagged_and_questioned() {
+        let mut data = started("*.");
+
+        data[CELLS] = 0b0001 | 0b1100 << 4;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
+
+    #[test]
+    fn opened_mine_in_progress() {
+        let mut data = started("*.");
+
+        data[CELLS] = 0b0011;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
+
+    #[test]
+    fn no_safe_position() {
+        let mut data = started("*.");
+
+        data[CELLS] = 0b0001 | 0b0001 << 4;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
+
+    #[test]
+    fn lost_position_mismatch() {
+        let mut data = started("*.");
+
+        data[STATUS] = 3;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+
+        let mut game = Game::from_layout("*.").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        let mut data = game.to_bytes();
+
+        data[STATUS] = 1;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
+
+    #[test]
+    fn lost_position_not_mined() {
+        let mut game = Game::from_layout("*.").expect("game created");
+
+        game.start().expect("Game started");
+        game.open(Position(0, 0)).expect("Position opened");
+
+        let mut data = game.to_bytes();
+
+        // x of the lost position, right after its presence marker
+        data[CELLS] = 1;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
+
+    #[test]
+    fn invalid_dimensions() {
+        let mut data = started("*.");
+
+        data[5..13].copy_from_slice(&0u64.to_le_bytes());
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+
+        data[5..13].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
+
+    #[test]
+    fn unknown_settings() {
+        let mut data = started("*.");
+
+        data[SETTINGS + 1] |= 0b1000;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
+
+    #[test]
+    fn wrapped_hex_odd_height() {
+        let mut data = started("*.");
+
+        data[SETTINGS + 1] |= 0b0001;
+        data[SETTINGS + 2] = 1;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
     }
-}
 
-#[cfg(test)]
-mod game_new {
-    use super::*;
+    #[test]
+    fn dirty_padding() {
+        let mut data = started("*..");
+
+        assert!(Game::from_bytes(&data).is_ok());
+
+        data[CELLS + 1] |= 0b0001 << 4;
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
+    }
 
     #[test]
-    fn create_new_game() {
-        let game = Game::new(100, 100).expect("game created");
+    fn unsupported_version() {
+        let mut data = mid_game().to_bytes();
 
-        assert_eq!(game.width, 100);
-        assert_eq!(game.height, 100);
-        assert_eq!(game.mine_positions.len(), 0);
-        assert_eq!(game.open_positions.len(), 0);
-        assert_eq!(game.flag_positions.len(), 0);
-        assert_eq!(game.status, Status::Configuration);
+        data[4] = 2;
+
+        assert_eq!(
+            Game::from_bytes(&data),
+            Err(GameError::UnsupportedVersion(2))
+        );
     }
 
     #[test]
-    fn zero_area() {
-        assert!(matches!(Game::new(0, 1), Err(GameError::ZeroFieldArea)));
+    fn wrong_magic() {
+        let mut data = mid_game().to_bytes();
+
+        data[0] = b'X';
+
+        assert_eq!(Game::from_bytes(&data), Err(GameError::InvalidSaveData));
     }
 }
 
 #[cfg(test)]
-mod game_is_in_bounds {
+mod game_board_hash {
     use super::*;
 
     #[test]
-    fn in_bounds() {
-        let game = Game::new(10, 10).expect("game created");
+    fn equal_boards() {
+        let first = Game::new_random(16, 16, 40, 3).expect("game created");
+        let second = Game::new_random(16, 16, 40, 3).expect("game created");
 
-        assert!(game.is_in_bounds(&Position(1, 1)));
+        assert_eq!(first.board_hash(), second.board_hash());
     }
 
     #[test]
-    fn out_of_bounds() {
-        let game = Game::new(10, 10).expect("game created");
+    fn relocated_mine() {
+        let first = Game::from_layout("*..\n...").expect("game created");
+        let second = Game::from_layout(".*.\n...").expect("game created");
+
+        assert_ne!(first.board_hash(), second.board_hash());
+    }
 
-        assert_eq!(game.is_in_bounds(&Position(100, 1)), false);
+    #[test]
+    fn different_dimensions() {
+        let first = Game::from_layout("*..\n...").expect("game created");
+        let second = Game::from_layout("*..").expect("game created");
+
+        assert_ne!(first.board_hash(), second.board_hash());
     }
 }
 
 #[cfg(test)]
-mod game_mine {
+mod game_render {
     use super::*;
 
     #[test]
-    fn set_mine_in_fresh_game() {
-        let mut game = Game::new(100, 100).expect("game created");
+    fn render_in_progress() {
+        let mut game = Game::new(4, 3).expect("game created");
 
-        let mine_position = Position(1, 1);
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine(Position(3, 2)).expect("Set mine");
+        game.start().expect("Game started");
 
-        game.mine(mine_position).expect("Set mine");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.open(Position(2, 0)).expect("Position opened");
 
-        assert!(game.mine_positions.contains(&mine_position));
+        assert_eq!(game.render(), "F1  \n.111\n....\n");
     }
 
     #[test]
-    fn set_mine_in_progress_game() {
-        let mut game = Game::new(10, 10).expect("game created");
+    fn render_lost() {
+        let mut game = Game::new(3, 2).expect("game created");
 
+        game.mine(Position(0, 0)).expect("Set mine");
+        game.mine(Position(2, 1)).expect("Set mine");
         game.start().expect("Game started");
 
-        assert_eq!(
-            game.mine(Position(1, 1)),
-            Err(GameError::IncorrectStatus(
-                Status::InProgress,
-                Status::Configuration
-            ))
-        );
-    }
-
-    #[test]
-    fn set_mine_twice() {
-        let mut game = Game::new(10, 10).expect("game created");
-
-        let mine_position = Position(1, 1);
+        game.open(Position(1, 0)).expect("Position opened");
+        game.open(Position(0, 0)).expect("Position opened");
 
-        game.mine(mine_position).expect("Set mine");
-        assert_eq!(game.mine(mine_position), Err(GameError::AlreadyMined));
+        assert_eq!(game.render(), "*2.\n..*\n");
     }
 
     #[test]
-    fn set_mine_out_of_bounds() {
-        let mut game = Game::new(10, 10).expect("game created");
+    fn render_large_count() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.mine_with_count(Position(0, 0), 12).expect("Set mine");
+        game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
 
-        assert_eq!(game.mine(Position(20, 5)), Err(GameError::OutOfBounds));
+        assert_eq!(game.render(), "*+\n");
     }
 }
 
 #[cfg(test)]
-mod game_start {
+mod game_to_ppm {
     use super::*;
 
     #[test]
-    fn start_fresh_game() {
-        let mut game = Game::new(10, 10).expect("game created");
+    fn header_and_size() {
+        let game = Game::new(4, 3).expect("game created");
+        let header = b"P6\n12 9\n255\n";
 
-        game.start().expect("Game started");
+        let image = game.to_ppm(3).expect("Image rendered");
 
-        assert_eq!(game.status, Status::InProgress);
+        assert!(image.starts_with(header));
+        assert_eq!(image.len(), header.len() + 4 * 3 * 3 * 3 * 3);
     }
 
     #[test]
-    fn start_already_started_game() {
-        let mut game = Game::new(1, 1).expect("game created");
+    fn distinct_colors() {
+        let mut game = Game::from_layout("*..\n...").expect("game created");
+        let header_len = b"P6\n3 2\n255\n".len();
 
         game.start().expect("Game started");
+        game.open(Position(1, 0)).expect("Position opened");
+        game.flag(Position(2, 1)).expect("Position flagged");
 
-        assert_eq!(
-            game.start(),
-            Err(GameError::IncorrectStatus(
-                Status::InProgress,
-                Status::Configuration
-            ))
-        );
+        let image = game.to_ppm(1).expect("Image rendered");
+        let pixels: Vec<&[u8]> = image[header_len..].chunks(3).collect();
+
+        // hidden, open with 1, hidden, hidden, hidden, flagged
+        assert_eq!(pixels.len(), 6);
+        assert_eq!(pixels[0], pixels[2]);
+        assert_ne!(pixels[0], pixels[1]);
+        assert_ne!(pixels[0], pixels[5]);
+        assert_ne!(pixels[1], pixels[5]);
+
+        game.open(Position(0, 0)).expect("Position opened");
+
+        let image = game.to_ppm(1).expect("Image rendered");
+        let mine = &image[header_len..header_len + 3];
+
+        assert!(pixels.iter().all(|pixel| *pixel != mine));
+    }
+
+    #[test]
+    fn size_overflow() {
+        let game = Game::new(4, 3).expect("game created");
+
+        assert_eq!(game.to_ppm(usize::MAX / 2), Err(GameError::AreaOverflow));
+        assert_eq!(game.to_ppm(usize::MAX / 24), Err(GameError::AreaOverflow));
     }
 }
 
 #[cfg(test)]
-mod game_open {
+mod game_neighbors {
     use super::*;
 
     #[test]
-    fn open_in_config_game() {
-        let mut game = Game::new(1, 1).expect("game created");
+    fn corner() {
+        let game = Game::new(5, 5).expect("game created");
 
         assert_eq!(
-            game.open(Position(1, 1)),
-            Err(GameError::IncorrectStatus(
-                Status::Configuration,
-                Status::InProgress
-            ))
+            game.neighbors(Position(0, 0)),
+            vec![Position(1, 0), Position(0, 1), Position(1, 1)]
         );
     }
 
     #[test]
-    fn open_safe_position() {
-        let mut game = Game::new(10, 10).expect("game created");
-
-        let mine_position = Position(1, 1);
-        let safe_position = Position(1, 2);
-
-        game.mine(mine_position).expect("Set mine");
-        game.start().expect("Game started");
-
-        game.open(safe_position).expect("Position opened");
+    fn edge() {
+        let game = Game::new(5, 5).expect("game created");
 
-        assert_eq!(game.status, Status::InProgress);
-        assert!(game.open_positions.contains(&safe_position));
+        assert_eq!(
+            game.neighbors(Position(4, 2)),
+            vec![
+                Position(3, 1),
+                Position(4, 1),
+                Position(3, 2),
+                Position(3, 3),
+                Position(4, 3)
+            ]
+        );
     }
 
     #[test]
-    fn open_mine_position() {
-        let mut game = Game::new(10, 10).expect("game created");
-
-        let mine_position = Position(1, 1);
-
-        game.mine(mine_position).expect("Set mine");
-        game.start().expect("Game started");
-
-        game.open(mine_position).expect("Position opened");
+    fn center() {
+        let game = Game::new(5, 5).expect("game created");
 
-        assert_eq!(game.status, Status::Lost);
+        assert_eq!(
+            game.neighbors(Position(2, 2)),
+            vec![
+                Position(1, 1),
+                Position(2, 1),
+                Position(3, 1),
+                Position(1, 2),
+                Position(3, 2),
+                Position(1, 3),
+                Position(2, 3),
+                Position(3, 3)
+            ]
+        );
     }
+}
 
-    #[test]
-    fn open_safe_position_twice() {
-        let mut game = Game::new(10, 10).expect("game created");
+#[cfg(test)]
+mod game_display {
+    use super::*;
 
-        let open = Position(1, 2);
+    #[test]
+    fn format_game() {
+        let mut game = Game::new(3, 2).expect("game created");
 
+        game.mine(Position(0, 0)).expect("Set mine");
         game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.open(Position(2, 1)).expect("Position opened");
 
-        game.open(open).expect("Position opened");
-
-        assert_eq!(game.open(open), Err(GameError::AlreadyOpened));
+        assert_eq!(format!("{}", game), "F1 \n.1 \n");
     }
+}
 
-    #[test]
-    fn open_flagged_position() {
-        let mut game = Game::new(10, 10).expect("game created");
-
-        let flag = Position(1, 2);
+#[cfg(test)]
+mod game_neighbors_with_offset {
+    use super::*;
+    use crate::position::NEIGHBOR_OFFSETS;
 
-        game.start().expect("Game started");
+    #[test]
+    fn center_position() {
+        let game = Game::new(3, 3).expect("game created");
 
-        game.flag(flag).expect("Position flagged");
-        game.open(flag).expect("Position opened");
+        let neighbours = game.neighbors_with_offset(Position(1, 1));
 
-        assert_eq!(game.flag_positions.contains(&flag), false);
-        assert!(game.open_positions.contains(&flag));
+        assert_eq!(neighbours.len(), 8);
+        for (neighbour, (x_dif, y_dif)) in neighbours {
+            assert_eq!(Position(1, 1).get_relative(x_dif, y_dif), Ok(neighbour));
+        }
+        for offset in NEIGHBOR_OFFSETS {
+            assert!(game
+                .neighbors_with_offset(Position(1, 1))
+                .iter()
+                .any(|(_, listed)| *listed == offset));
+        }
     }
 
     #[test]
-    fn out_of_bounds() {
-        let mut game = Game::new(10, 10).expect("game created");
-        game.start().expect("Game started");
+    fn corner_position() {
+        let game = Game::new(3, 3).expect("game created");
 
-        assert_eq!(game.open(Position(11, 10)), Err(GameError::OutOfBounds));
+        assert_eq!(
+            game.neighbors_with_offset(Position(0, 0)),
+            vec![
+                (Position(1, 0), (1, 0)),
+                (Position(0, 1), (0, 1)),
+                (Position(1, 1), (1, 1))
+            ]
+        );
     }
+}
+
+#[cfg(test)]
+mod game_neighbors_with_state {
+    use super::*;
 
     #[test]
-    fn win_game() {
-        let mut game = Game::new(1, 2).expect("game created");
+    fn center() {
+        let mut game = Game::from_layout("*..\n...\n..*").expect("game created");
 
         game.start().expect("Game started");
-
         game.flag(Position(0, 0)).expect("Position flagged");
-        game.open(Position(0, 1)).expect("Position opened");
+        game.question(Position(1, 0)).expect("Position questioned");
+        game.open(Position(1, 1)).expect("Position opened");
+        game.open(Position(2, 1)).expect("Position opened");
 
-        assert!(matches!(game.status, Status::Won));
+        assert_eq!(
+            game.neighbors_with_state(Position(1, 1)),
+            vec![
+                (Position(0, 0), CellState::Flagged),
+                (Position(1, 0), CellState::Questioned),
+                (Position(2, 0), CellState::Hidden),
+                (Position(0, 1), CellState::Hidden),
+                (Position(2, 1), CellState::Open(1)),
+                (Position(0, 2), CellState::Hidden),
+                (Position(1, 2), CellState::Hidden),
+                (Position(2, 2), CellState::Hidden)
+            ]
+        );
     }
 }
 
 #[cfg(test)]
-mod game_flag {
+mod game_adjacency_grid {
     use super::*;
 
-    #[test]
-    fn flag_position() {
-        let mut game = Game::new(10, 10).expect("game created");
+    fn assert_matches_adjacent_mines(game: &Game) {
+        let grid = game.adjacency_grid();
 
-        let flag_position = Position(1, 1);
+        assert_eq!(grid.len(), game.height);
 
-        game.start().expect("Game started");
+        for position in game.positions() {
+            assert_eq!(
+                Ok(grid[position.1][position.0]),
+                game.adjacent_mines(position)
+            );
+        }
+    }
 
-        game.flag(flag_position).expect("Position flagged");
+    #[test]
+    fn random_board() {
+        let game = Game::new_random(16, 9, 40, 7).expect("game created");
 
-        assert!(game.flag_positions.contains(&flag_position));
+        assert_matches_adjacent_mines(&game);
     }
 
     #[test]
-    fn flag_before_start() {
-        let mut game = Game::new(10, 10).expect("game created");
+    fn stacked_mines() {
+        let mut game = Game::new(4, 4).expect("game created");
 
-        assert_eq!(
-            game.flag(Position(1, 1)),
-            Err(GameError::IncorrectStatus(
-                Status::Configuration,
-                Status::InProgress
-            ))
-        );
+        game.mine_with_count(Position(1, 1), 3).expect("Set mine");
+        game.mine(Position(2, 1)).expect("Set mine");
+
+        assert_matches_adjacent_mines(&game);
     }
 
     #[test]
-    fn flag_position_twice() {
-        let mut game = Game::new(10, 10).expect("game created");
+    fn wrapped_hex_board() {
+        for height in [6, 8] {
+            let mut game = Game::new_random(6, height, 10, 3).expect("game created");
 
-        let flag_position = Position(1, 1);
+            game.set_topology(Topology::Hex).expect("Topology set");
+            game.set_wrap(true).expect("Wrap set");
 
-        game.start().expect("Game started");
+            assert_matches_adjacent_mines(&game);
+        }
+    }
+}
 
-        game.flag(flag_position).expect("Position flagged");
+#[cfg(test)]
+mod game_adjacent_mines {
+    use super::*;
+
+    #[test]
+    fn zero_mines() {
+        let game = Game::new(5, 5).expect("game created");
 
-        assert_eq!(game.flag(flag_position), Err(GameError::AlreadyFlagged));
+        assert_eq!(game.adjacent_mines(Position(3, 3)), Ok(0));
     }
 
     #[test]
-    fn flag_open_position() {
-        let mut game = Game::new(10, 10).expect("game created");
+    fn some_mines() {
+        let mut game = Game::new(5, 5).expect("game created");
 
-        let open = Position(1, 1);
+        game.mine(Position(2, 3)).expect("Set mine");
+        game.mine(Position(4, 4)).expect("Set mine");
 
-        game.start().expect("Game started");
+        assert_eq!(game.adjacent_mines(Position(3, 3)), Ok(2));
+    }
 
-        game.open(open).expect("Position opened");
+    #[test]
+    fn border_position() {
+        let mut game = Game::new(5, 5).expect("game created");
+
+        game.mine(Position(0, 2)).expect("Set mine");
+        game.mine(Position(1, 3)).expect("Set mine");
+        game.mine(Position(0, 4)).expect("Set mine");
 
-        assert_eq!(game.flag(open), Err(GameError::AlreadyOpened));
+        assert_eq!(game.adjacent_mines(Position(0, 3)), Ok(3));
     }
 
     #[test]
-    fn out_of_bounds() {
-        let mut game = Game::new(10, 10).expect("game created");
+    fn mine_surrounded_by_mines() {
+        let mut game = Game::new(5, 5).expect("game created");
 
-        game.start().expect("Game started");
+        game.mine(Position(2, 2)).expect("Set mine");
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(3, 2)).expect("Set mine");
+        game.mine(Position(2, 3)).expect("Set mine");
 
-        assert_eq!(game.flag(Position(12, 8)), Err(GameError::OutOfBounds));
+        assert_eq!(game.adjacent_mines(Position(2, 2)), Ok(3));
     }
 
     #[test]
-    fn win_game() {
-        let mut game = Game::new(1, 2).expect("game created");
-
-        let mine = Position(0, 1);
+    fn corner_position() {
+        let mut game = Game::new(3, 3).expect("game created");
 
-        game.mine(mine).expect("Set mine");
+        game.mine(Position(1, 0)).expect("Set mine");
+        game.mine(Position(0, 1)).expect("Set mine");
+        game.mine(Position(1, 1)).expect("Set mine");
+        game.mine(Position(2, 2)).expect("Set mine");
 
-        game.start().expect("Game started");
+        assert_eq!(game.adjacent_mines(Position(0, 0)), Ok(3));
+    }
 
-        game.open(Position(0, 0)).expect("Position opened");
-        game.flag(mine).expect("Position flagged");
+    #[test]
+    fn out_of_bounds() {
+        let game = Game::new(5, 5).expect("game created");
 
-        assert!(matches!(game.status, Status::Won));
+        assert_eq!(
+            game.adjacent_mines(Position(5, 1)),
+            Err(GameError::OutOfBounds(Position(5, 1)))
+        );
     }
 }
 
 #[cfg(test)]
-mod game_check_proximity {
+mod game_count_flagged_neighbors {
     use super::*;
 
     #[test]
-    fn zero_mines() {
-        let mut game = Game::new(5, 5).expect("game created");
+    fn no_flags() {
+        let mut game = Game::new(3, 3).expect("game created");
 
-        game.start().expect("game started");
+        game.start().expect("Game started");
+        game.flag(Position(1, 1)).expect("Position flagged");
 
-        assert_eq!(game.check_proximity(Position(3, 3)), Ok(0));
+        assert_eq!(game.count_flagged_neighbors(Position(1, 1)), Ok(0));
     }
 
     #[test]
-    fn some_mines() {
-        let mut game = Game::new(5, 5).expect("game created");
+    fn some_flags() {
+        let mut game = Game::new(3, 3).expect("game created");
 
-        game.mine(Position(2, 3)).expect("Set mine");
-        game.mine(Position(4, 4)).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(2, 1)).expect("Position flagged");
+
+        assert_eq!(game.count_flagged_neighbors(Position(1, 1)), Ok(2));
+        assert_eq!(game.count_flagged_neighbors(Position(0, 2)), Ok(0));
+    }
+
+    #[test]
+    fn all_neighbors_flagged() {
+        let mut game = Game::new(3, 3).expect("game created");
 
-        game.start().expect("game started");
+        game.start().expect("Game started");
+        for neighbour in game.neighbors(Position(1, 1)) {
+            game.flag(neighbour).expect("Position flagged");
+        }
 
-        assert_eq!(game.check_proximity(Position(3, 3)), Ok(2));
+        assert_eq!(game.count_flagged_neighbors(Position(1, 1)), Ok(8));
     }
 
     #[test]
-    fn border_position() {
-        let mut game = Game::new(5, 5).expect("game created");
+    fn flagged_stack() {
+        let mut game = Game::new(3, 3).expect("game created");
 
-        game.mine(Position(0, 2)).expect("Set mine");
-        game.mine(Position(1, 3)).expect("Set mine");
-        game.mine(Position(0, 4)).expect("Set mine");
+        game.mine_with_count(Position(0, 0), 3).expect("Set mine");
+        game.start().expect("Game started");
+        game.flag(Position(0, 0)).expect("Position flagged");
+        game.flag(Position(2, 2)).expect("Position flagged");
+
+        assert_eq!(game.count_flagged_neighbors(Position(1, 1)), Ok(4));
+    }
 
-        game.start().expect("game started");
+    #[test]
+    fn out_of_bounds() {
+        let game = Game::new(3, 3).expect("game created");
 
-        assert_eq!(game.check_proximity(Position(0, 3)), Ok(3));
+        assert_eq!(
+            game.count_flagged_neighbors(Position(3, 3)),
+            Err(GameError::OutOfBounds(Position(3, 3)))
+        );
     }
 }
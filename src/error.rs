@@ -1,15 +1,54 @@
 use core::fmt;
 
+use crate::position::Position;
 use crate::status::Status;
 
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameError {
     IncorrectStatus(Status, Status),
+    NotStarted,
+    GameOver,
     ZeroFieldArea,
-    OutOfBounds,
-    AlreadyMined,
-    AlreadyOpened,
-    AlreadyFlagged,
+    AreaOverflow,
+    TooManyMines,
+    InvalidMineCount(u8),
+    RaggedLayout,
+    UnknownLayoutSymbol(char),
+    OutOfBounds(Position),
+    Underflow(Position),
+    AlreadyMined(Position),
+    NotMined(Position),
+    BoardFull,
+    AlreadyOpened(Position),
+    AlreadyFlagged(Position),
+    CellFlagged,
+    FlagLimitReached,
+    NotFlagged,
+    AlreadyQuestioned,
+    NotChordable,
+    NothingToUndo,
+    InvalidMoveLog,
+    UnsupportedVersion(u8),
+    InvalidSaveData,
+    InvalidRegion,
+    HexWrapOddHeight,
+    GenerationFailed,
+}
+
+impl GameError {
+    /// Position which caused the error, if the error is about one.
+    pub fn position(&self) -> Option<Position> {
+        match *self {
+            GameError::OutOfBounds(position)
+            | GameError::Underflow(position)
+            | GameError::AlreadyMined(position)
+            | GameError::NotMined(position)
+            | GameError::AlreadyOpened(position)
+            | GameError::AlreadyFlagged(position) => Some(position),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for GameError {
@@ -20,11 +59,127 @@ impl fmt::Display for GameError {
                 "game in status {:?}, but should be in {:?}",
                 given_status, corr_status
             ),
-            GameError::OutOfBounds => write!(f, "position out of bounds"),
-            GameError::AlreadyMined => write!(f, "position already have mine"),
-            GameError::AlreadyOpened => write!(f, "position already opened"),
-            GameError::AlreadyFlagged => write!(f, "position already have flag"),
+            GameError::NotStarted => write!(f, "game isn't started yet"),
+            GameError::GameOver => write!(f, "game is already over"),
+            GameError::OutOfBounds(position) => {
+                write!(f, "position ({}, {}) out of bounds", position.0, position.1)
+            }
+            GameError::Underflow(position) => write!(
+                f,
+                "position ({}, {}) moved below zero",
+                position.0, position.1
+            ),
+            GameError::AlreadyMined(position) => {
+                write!(
+                    f,
+                    "position ({}, {}) already have mine",
+                    position.0, position.1
+                )
+            }
+            GameError::NotMined(position) => {
+                write!(
+                    f,
+                    "position ({}, {}) doesn't have mine",
+                    position.0, position.1
+                )
+            }
+            GameError::BoardFull => write!(f, "field needs at least one position without mine"),
+            GameError::AlreadyOpened(position) => {
+                write!(
+                    f,
+                    "position ({}, {}) already opened",
+                    position.0, position.1
+                )
+            }
+            GameError::AlreadyFlagged(position) => {
+                write!(
+                    f,
+                    "position ({}, {}) already have flag",
+                    position.0, position.1
+                )
+            }
+            GameError::CellFlagged => write!(f, "flagged position can't be opened"),
+            GameError::FlagLimitReached => write!(f, "no more flags than mines allowed"),
+            GameError::NotFlagged => write!(f, "position doesn't have flag"),
+            GameError::AlreadyQuestioned => write!(f, "position already have question mark"),
+            GameError::NothingToUndo => write!(f, "no moves to undo"),
+            GameError::InvalidMoveLog => write!(f, "move log line is malformed"),
+            GameError::UnsupportedVersion(version) => {
+                write!(f, "save format version {} isn't supported", version)
+            }
+            GameError::InvalidSaveData => write!(f, "save data is truncated or damaged"),
+            GameError::InvalidRegion => write!(f, "region corners are in wrong order"),
+            GameError::HexWrapOddHeight => {
+                write!(f, "wrapping hex field needs even number of rows")
+            }
+            GameError::GenerationFailed => {
+                write!(f, "no board in requested range within given attempts")
+            }
+            GameError::NotChordable => {
+                write!(
+                    f,
+                    "position isn't opened or flags around don't match its count"
+                )
+            }
             GameError::ZeroFieldArea => write!(f, "field area is zero"),
+            GameError::AreaOverflow => write!(f, "field area doesn't fit in usize"),
+            GameError::TooManyMines => write!(f, "too many mines for field area"),
+            GameError::InvalidMineCount(count) => {
+                write!(f, "{} mines can't be stacked on position", count)
+            }
+            GameError::RaggedLayout => write!(f, "layout rows have different length"),
+            GameError::UnknownLayoutSymbol(symbol) => {
+                write!(f, "unknown layout symbol {:?}", symbol)
+            }
         }
     }
 }
+
+#[cfg(feature = "std")]
+impl std::error::Error for GameError {}
+
+#[cfg(test)]
+mod game_error_display {
+    use super::*;
+
+    #[test]
+    fn incorrect_status() {
+        let error = GameError::IncorrectStatus(Status::Won, Status::InProgress);
+
+        assert_eq!(
+            error.to_string(),
+            "game in status Won, but should be in InProgress"
+        );
+    }
+}
+
+#[cfg(test)]
+mod game_error_position {
+    use super::*;
+
+    #[test]
+    fn position_error() {
+        assert_eq!(
+            GameError::AlreadyOpened(Position(3, 4)).position(),
+            Some(Position(3, 4))
+        );
+    }
+
+    #[test]
+    fn other_error() {
+        assert_eq!(GameError::NothingToUndo.position(), None);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod game_error_error {
+    use super::*;
+
+    #[test]
+    fn boxed_error() {
+        let error: Box<dyn std::error::Error> = Box::new(GameError::OutOfBounds(Position(1, 2)));
+
+        assert_eq!(error.to_string(), "position (1, 2) out of bounds");
+        assert!(error.source().is_none());
+    }
+}
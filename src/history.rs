@@ -0,0 +1,35 @@
+use alloc::vec::Vec;
+
+use crate::position::Position;
+use crate::status::Status;
+
+/// Player move recorded in game history together with everything it
+/// changed, so it can be reverted with `Game::undo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+    /// Open of `position`: cells opened by it (including cascade), whether
+    /// flag was removed from `position`, cells which lost question mark,
+    /// where mine was moved by safe first open, status before the move and
+    /// whether mine was flagged instead of losing (training mode or mercy).
+    Open {
+        position: Position,
+        opened: Vec<Position>,
+        flagged: bool,
+        questioned: Vec<Position>,
+        relocated: Option<Position>,
+        status: Status,
+        forgiven: bool,
+    },
+    /// Flag placed on `position`, replacing question mark if `questioned`.
+    Flag {
+        position: Position,
+        questioned: bool,
+    },
+    Unflag(Position),
+    /// Question mark placed on `position`, replacing flag if `flagged`.
+    Question {
+        position: Position,
+        flagged: bool,
+    },
+}
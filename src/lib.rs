@@ -0,0 +1,52 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+mod action;
+mod byte_reader;
+mod cell_state;
+mod connectivity;
+mod core;
+mod error;
+mod history;
+mod neighbor_cache;
+mod open_callback;
+mod open_outcome;
+mod position;
+mod random;
+mod status;
+mod timer;
+mod topology;
+
+pub use crate::action::Action;
+pub use crate::cell_state::CellState;
+pub use crate::connectivity::Connectivity;
+pub use crate::core::Game;
+pub use crate::error::GameError;
+pub use crate::history::Move;
+pub use crate::open_outcome::OpenOutcome;
+pub use crate::position::{Position, PositionSet, NEIGHBOR_OFFSETS};
+pub use crate::status::Status;
+pub use crate::topology::Topology;
+
+#[cfg(test)]
+mod public_api {
+    use crate::{Game, GameError, Position, Status};
+
+    #[test]
+    fn play_through_crate_root() {
+        let mut game = Game::new(2, 1).expect("game created");
+
+        game.mine(Position::new(0, 0)).expect("Set mine");
+        game.start().expect("Game started");
+
+        assert_eq!(
+            game.open(Position::new(2, 0)),
+            Err(GameError::OutOfBounds(Position::new(2, 0)))
+        );
+
+        game.open(Position::new(1, 0)).expect("Position opened");
+
+        assert_eq!(game.status(), Status::Won);
+    }
+}
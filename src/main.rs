@@ -1,8 +1,3 @@
-mod core;
-mod error;
-mod position;
-mod status;
-
 fn main() {
     println!("Hello, world!");
 }
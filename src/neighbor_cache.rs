@@ -0,0 +1,71 @@
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+use crate::position::Position;
+
+// Precomputed neighbours of every position, indexed by `y * width + x`, built
+// on first lookup. It is only a speed up, so games compare equal whether it
+// is built or not. Without `std` there is no thread safe lazy cell, so it
+// keeps nothing and neighbours are always computed.
+#[derive(Clone, Default)]
+pub struct NeighborCache {
+    #[cfg(feature = "std")]
+    neighbors: OnceLock<Vec<Vec<Position>>>,
+}
+
+#[cfg(feature = "std")]
+impl NeighborCache {
+    pub fn get_or_build(
+        &self,
+        index: usize,
+        build: impl FnOnce() -> Vec<Vec<Position>>,
+    ) -> Option<&[Position]> {
+        self.neighbors
+            .get_or_init(build)
+            .get(index)
+            .map(Vec::as_slice)
+    }
+
+    pub fn clear(&mut self) {
+        self.neighbors = OnceLock::new();
+    }
+
+    pub fn is_built(&self) -> bool {
+        self.neighbors.get().is_some()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl NeighborCache {
+    pub fn get_or_build(
+        &self,
+        _index: usize,
+        _build: impl FnOnce() -> Vec<Vec<Position>>,
+    ) -> Option<&[Position]> {
+        None
+    }
+
+    pub fn clear(&mut self) {}
+
+    pub fn is_built(&self) -> bool {
+        false
+    }
+}
+
+impl PartialEq for NeighborCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for NeighborCache {}
+
+impl fmt::Debug for NeighborCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NeighborCache")
+            .field("built", &self.is_built())
+            .finish()
+    }
+}
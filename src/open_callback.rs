@@ -0,0 +1,55 @@
+use alloc::boxed::Box;
+use core::fmt;
+use core::panic::AssertUnwindSafe;
+
+use crate::position::Position;
+
+type Callback = Box<dyn FnMut(Position, u8) + Send + Sync>;
+
+// Callback notified about every position opened, cascade included. Closure
+// can't be cloned or compared, so clones start without it and games compare
+// equal whether it is set or not. It has to be `Send` and `Sync`, so game
+// keeps being both. Game also stays unwind safe: closure panicking mid-open
+// leaves game as any other panic there would, so it is asserted here.
+#[derive(Default)]
+pub struct OpenCallback {
+    callback: Option<AssertUnwindSafe<Callback>>,
+}
+
+impl OpenCallback {
+    pub fn set(&mut self, callback: Callback) {
+        self.callback = Some(AssertUnwindSafe(callback));
+    }
+
+    pub fn call(&mut self, position: Position, count: u8) {
+        if let Some(callback) = self.callback.as_mut() {
+            callback(position, count);
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.callback.is_some()
+    }
+}
+
+impl Clone for OpenCallback {
+    fn clone(&self) -> Self {
+        OpenCallback::default()
+    }
+}
+
+impl PartialEq for OpenCallback {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for OpenCallback {}
+
+impl fmt::Debug for OpenCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpenCallback")
+            .field("set", &self.is_set())
+            .finish()
+    }
+}
@@ -0,0 +1,14 @@
+use alloc::vec::Vec;
+
+use crate::position::Position;
+use crate::status::Status;
+
+/// What `Game::open` changed: positions it opened with their mine counts
+/// (including cascade, in row-major order), whether the opened position held
+/// a mine and status after the move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenOutcome {
+    pub opened: Vec<(Position, u8)>,
+    pub hit_mine: bool,
+    pub status: Status,
+}
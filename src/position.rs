@@ -1,46 +1,74 @@
 use crate::error::GameError;
 
+/// Set of positions used by the game, iterated in row-major order.
+pub type PositionSet = alloc::collections::BTreeSet<Position>;
+
+/// Relative coordinates of the eight neighbours on square field.
+pub const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Positions are ordered row-major: by `y` first, then by `x`.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position(pub(crate) usize, pub(crate) usize);
 
+impl Ord for Position {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.1, self.0).cmp(&(other.1, other.0))
+    }
+}
+
+impl PartialOrd for Position {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Position {
-    pub fn get_relative(&self, x_dif: isize, y_dif: isize) -> Result<Position, GameError> {
-        let x: Option<usize>;
-        let y: Option<usize>;
-
-        if x_dif.is_negative() {
-            x = self.0.checked_sub(
-                x_dif
-                    .checked_neg()
-                    .unwrap_or(0isize)
-                    .try_into()
-                    .unwrap_or(usize::MIN),
-            );
-        } else {
-            x = self.0.checked_add(x_dif.try_into().unwrap_or(usize::MAX));
-        }
+    /// Creates position from its coordinates, bounds are checked by the game.
+    ///
+    /// ```
+    /// use minesweeper::Position;
+    ///
+    /// let position = Position::new(3, 7);
+    ///
+    /// assert_eq!(position.x(), 3);
+    /// assert_eq!(position.y(), 7);
+    /// ```
+    pub fn new(x: usize, y: usize) -> Position {
+        Position(x, y)
+    }
 
-        if x.is_none() {
-            return Err(GameError::OutOfBounds);
-        }
+    pub fn x(&self) -> usize {
+        self.0
+    }
 
-        if y_dif.is_negative() {
-            y = self.1.checked_sub(
-                y_dif
-                    .checked_neg()
-                    .unwrap_or(0isize)
-                    .try_into()
-                    .unwrap_or(usize::MIN),
-            );
-        } else {
-            y = self.1.checked_add(y_dif.try_into().unwrap_or(usize::MAX));
-        }
+    pub fn y(&self) -> usize {
+        self.1
+    }
 
-        if y.is_none() {
-            return Err(GameError::OutOfBounds);
-        }
+    /// Position moved by given differences. Moving below zero gives
+    /// `Underflow`, past `usize::MAX` gives `OutOfBounds`, both holding the
+    /// original position as the target can't be represented.
+    pub fn get_relative(&self, x_dif: isize, y_dif: isize) -> Result<Position, GameError> {
+        let x = self.0.checked_add_signed(x_dif);
+        let y = self.1.checked_add_signed(y_dif);
 
-        Ok(Position(x.unwrap(), y.unwrap()))
+        match (x, y) {
+            (Some(x), Some(y)) => Ok(Position(x, y)),
+            // adding negative difference fails only below zero
+            (None, _) if x_dif.is_negative() => Err(GameError::Underflow(*self)),
+            (_, None) if y_dif.is_negative() => Err(GameError::Underflow(*self)),
+            _ => Err(GameError::OutOfBounds(*self)),
+        }
     }
 }
 
@@ -62,7 +90,88 @@ mod position_get_relative {
     fn get_relative_with_oob_negative() {
         assert_eq!(
             Position(2, 2).get_relative(-100, -100),
-            Err(GameError::OutOfBounds)
+            Err(GameError::Underflow(Position(2, 2)))
+        );
+    }
+
+    #[test]
+    fn get_relative_with_oob_positive() {
+        assert_eq!(
+            Position(usize::MAX, 2).get_relative(1, 0),
+            Err(GameError::OutOfBounds(Position(usize::MAX, 2)))
         );
     }
+
+    #[test]
+    fn get_relative_with_min_offset() {
+        assert_eq!(
+            Position(2, 2).get_relative(0, isize::MIN),
+            Err(GameError::Underflow(Position(2, 2)))
+        );
+    }
+}
+
+#[cfg(test)]
+mod position_neighbor_offsets {
+    use super::*;
+
+    #[test]
+    fn eight_offsets() {
+        for x_dif in -1..=1 {
+            for y_dif in -1..=1 {
+                assert_eq!(
+                    NEIGHBOR_OFFSETS.contains(&(x_dif, y_dif)),
+                    (x_dif, y_dif) != (0, 0)
+                );
+            }
+        }
+        assert_eq!(NEIGHBOR_OFFSETS.len(), 8);
+    }
+}
+
+#[cfg(test)]
+mod position_ord {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn sort_row_major() {
+        let mut positions = vec![
+            Position(1, 1),
+            Position(2, 0),
+            Position(0, 1),
+            Position(0, 0),
+            Position(1, 0),
+        ];
+
+        positions.sort();
+
+        assert_eq!(
+            positions,
+            vec![
+                Position(0, 0),
+                Position(1, 0),
+                Position(2, 0),
+                Position(0, 1),
+                Position(1, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn row_before_column() {
+        assert!(Position(5, 0) < Position(0, 1));
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod position_serde {
+    use super::*;
+
+    #[test]
+    fn serialize_as_sequence() {
+        let json = serde_json::to_string(&Position(3, 4)).expect("position serialized");
+
+        assert_eq!(json, "[3,4]");
+    }
 }
@@ -0,0 +1,63 @@
+// Small xorshift generator, enough for scattering mines without pulling in
+// a dependency. Not suitable for anything that needs real randomness.
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> XorShift {
+        // xorshift gets stuck on zero state, so seed is mixed first
+        let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        state ^= state >> 31;
+
+        if state == 0 {
+            state = 0x9E37_79B9_7F4A_7C15;
+        }
+
+        XorShift { state }
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    pub fn below(&mut self, upper: usize) -> usize {
+        (self.next() % upper as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod xorshift_next {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut first = XorShift::new(42);
+        let mut second = XorShift::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(first.next(), second.next());
+        }
+    }
+
+    #[test]
+    fn zero_seed() {
+        let mut generator = XorShift::new(0);
+
+        assert_ne!(generator.next(), 0);
+    }
+
+    #[test]
+    fn below_upper_bound() {
+        let mut generator = XorShift::new(7);
+
+        for _ in 0..100 {
+            assert!(generator.below(5) < 5);
+        }
+    }
+}
@@ -1,6 +1,5 @@
-use core::fmt;
-
-#[derive(PartialEq, Copy, Clone, Eq)]
+#[derive(Debug, PartialEq, Copy, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Status {
     Configuration,
     InProgress,
@@ -8,8 +7,15 @@ pub enum Status {
     Lost,
 }
 
-impl fmt::Debug for Status {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "test")
+#[cfg(test)]
+mod status_debug {
+    use super::*;
+
+    #[test]
+    fn variant_names() {
+        assert_eq!(format!("{:?}", Status::Configuration), "Configuration");
+        assert_eq!(format!("{:?}", Status::InProgress), "InProgress");
+        assert_eq!(format!("{:?}", Status::Won), "Won");
+        assert_eq!(format!("{:?}", Status::Lost), "Lost");
     }
 }
@@ -0,0 +1,77 @@
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+// Measures game duration. Without `std` there is no clock, so it keeps
+// nothing and every call is a no-op. Start times depend on the wall clock,
+// so games compare equal whatever their timers say.
+#[derive(Debug, Clone, Default)]
+pub struct Timer {
+    #[cfg(feature = "std")]
+    started_at: Option<Instant>,
+    #[cfg(feature = "std")]
+    finished_at: Option<Instant>,
+}
+
+#[cfg(feature = "std")]
+impl Timer {
+    pub fn start(&mut self) {
+        self.started_at = Some(Instant::now());
+        self.finished_at = None;
+    }
+
+    pub fn stop(&mut self) {
+        self.finished_at = Some(Instant::now());
+    }
+
+    pub fn resume(&mut self) {
+        self.finished_at = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.started_at = None;
+        self.finished_at = None;
+    }
+
+    pub fn elapsed(&self) -> Option<Duration> {
+        let started_at = self.started_at?;
+        let until = self.finished_at.unwrap_or_else(Instant::now);
+
+        Some(until.saturating_duration_since(started_at))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Timer {
+    pub fn start(&mut self) {}
+
+    pub fn stop(&mut self) {}
+
+    pub fn resume(&mut self) {}
+
+    pub fn clear(&mut self) {}
+}
+
+impl PartialEq for Timer {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Timer {}
+
+#[cfg(all(test, feature = "std"))]
+mod timer_eq {
+    use super::*;
+
+    #[test]
+    fn different_start_times() {
+        let mut first = Timer::default();
+        let mut second = Timer::default();
+
+        first.start();
+        std::thread::sleep(Duration::from_millis(2));
+        second.start();
+
+        assert_eq!(first, second);
+    }
+}
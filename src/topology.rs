@@ -0,0 +1,49 @@
+use crate::position::NEIGHBOR_OFFSETS;
+
+/// Shape of field cells, decides which positions are neighbours.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology {
+    /// Square cells with eight neighbours.
+    #[default]
+    Square,
+    /// Hexagonal cells with six neighbours, laid out in rows where every odd
+    /// row is shifted right by half a cell.
+    Hex,
+}
+
+const HEX_EVEN_ROW_OFFSETS: [(isize, isize); 6] =
+    [(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)];
+
+const HEX_ODD_ROW_OFFSETS: [(isize, isize); 6] =
+    [(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+
+impl Topology {
+    /// Relative coordinates of neighbours for position in given row.
+    pub fn offsets(&self, row: usize) -> &'static [(isize, isize)] {
+        match self {
+            Topology::Square => &NEIGHBOR_OFFSETS,
+            Topology::Hex if row.is_multiple_of(2) => &HEX_EVEN_ROW_OFFSETS,
+            Topology::Hex => &HEX_ODD_ROW_OFFSETS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod topology_offsets {
+    use super::*;
+
+    #[test]
+    fn square() {
+        assert_eq!(Topology::Square.offsets(0).len(), 8);
+        assert_eq!(Topology::Square.offsets(1), Topology::Square.offsets(0));
+    }
+
+    #[test]
+    fn hex_rows() {
+        assert_eq!(Topology::Hex.offsets(0).len(), 6);
+        assert!(Topology::Hex.offsets(0).contains(&(-1, -1)));
+        assert!(Topology::Hex.offsets(1).contains(&(1, 1)));
+        assert!(!Topology::Hex.offsets(1).contains(&(-1, -1)));
+    }
+}